@@ -9,7 +9,25 @@ pub struct ExtendedVftProgram(());
 #[program]
 impl ExtendedVftProgram {
     pub fn new(name: String, symbol: String, decimals: u8) -> Self {
-        ExtendedService::init(name, symbol, decimals);
+        ExtendedService::init(name, symbol, decimals, None);
+        Self(())
+    }
+
+    /// Same as `new`, but minting can never take total supply above `max_supply`.
+    pub fn new_with_max_supply(
+        name: String,
+        symbol: String,
+        decimals: u8,
+        max_supply: U256,
+    ) -> Self {
+        ExtendedService::init(name, symbol, decimals, Some(max_supply));
+        Self(())
+    }
+
+    /// Same as `new`, but the inherited methods panic on errors instead of
+    /// returning them, for callers relying on error replies.
+    pub fn new_with_panicking_errors(name: String, symbol: String, decimals: u8) -> Self {
+        ExtendedService::init(name, symbol, decimals, None).with_panicking_errors();
         Self(())
     }
 
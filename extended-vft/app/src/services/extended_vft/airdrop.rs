@@ -0,0 +1,268 @@
+use super::utils::{Error, Result};
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use sails_rs::{collections::HashMap, prelude::*};
+
+pub type Hash = [u8; 32];
+
+/// Tokens escrowed by `funder` to be claimed by accounts listed in the Merkle
+/// tree with `root` until `expiry` (block timestamp in ms).
+pub struct Campaign {
+    pub funder: ActorId,
+    pub root: Hash,
+    pub remaining: U256,
+    pub expiry: u64,
+    /// Bitmap of claimed indexes, 64 per word.
+    pub claimed: HashMap<u32, u64>,
+}
+
+#[derive(Default)]
+pub struct Airdrops {
+    pub last_id: u64,
+    pub campaigns: HashMap<u64, Campaign>,
+}
+
+fn blake2b_256(data: &[u8]) -> Hash {
+    Blake2b::<U32>::digest(data).into()
+}
+
+/// Leaf of the tree for `account` claiming `amount` at `index`. Leaves hash
+/// 68 bytes and nodes 64, so a node can't be passed off as a leaf.
+pub fn leaf(index: u32, account: ActorId, amount: U256) -> Hash {
+    blake2b_256(&(index, account, amount).encode())
+}
+
+/// Checks `proof` of `leaf` against `root`, pairs are hashed sorted.
+pub fn verify(proof: &[Hash], root: Hash, leaf: Hash) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let (left, right) = if node <= *sibling {
+            (node, *sibling)
+        } else {
+            (*sibling, node)
+        };
+        blake2b_256(&[left, right].concat())
+    });
+
+    computed == root
+}
+
+pub fn create_campaign(
+    airdrops: &mut Airdrops,
+    funder: ActorId,
+    root: Hash,
+    amount: U256,
+    expiry: u64,
+    now: u64,
+) -> Result<u64> {
+    if expiry <= now {
+        return Err(Error::CampaignExpired);
+    }
+
+    let id = airdrops
+        .last_id
+        .checked_add(1)
+        .ok_or(Error::NumericOverflow)?;
+
+    airdrops.last_id = id;
+    airdrops.campaigns.insert(
+        id,
+        Campaign {
+            funder,
+            root,
+            remaining: amount,
+            expiry,
+            claimed: HashMap::new(),
+        },
+    );
+
+    Ok(id)
+}
+
+pub fn is_claimed(airdrops: &Airdrops, campaign: u64, index: u32) -> bool {
+    airdrops.campaigns.get(&campaign).is_some_and(|campaign| {
+        campaign
+            .claimed
+            .get(&(index / 64))
+            .is_some_and(|word| word & (1 << (index % 64)) != 0)
+    })
+}
+
+pub fn claim(
+    airdrops: &mut Airdrops,
+    campaign: u64,
+    index: u32,
+    account: ActorId,
+    amount: U256,
+    proof: &[Hash],
+    now: u64,
+) -> Result<()> {
+    let campaign = airdrops
+        .campaigns
+        .get_mut(&campaign)
+        .ok_or(Error::NonexistentCampaign)?;
+
+    if now > campaign.expiry {
+        return Err(Error::CampaignExpired);
+    }
+
+    let word = campaign.claimed.entry(index / 64).or_default();
+    let bit = 1 << (index % 64);
+
+    if *word & bit != 0 {
+        return Err(Error::AlreadyClaimed);
+    }
+
+    if !verify(proof, campaign.root, leaf(index, account, amount)) {
+        return Err(Error::InvalidProof);
+    }
+
+    campaign.remaining = campaign
+        .remaining
+        .checked_sub(amount)
+        .ok_or(Error::InsufficientBalance)?;
+    *word |= bit;
+
+    Ok(())
+}
+
+/// Closes an expired campaign, returning its funder and unclaimed amount.
+pub fn reclaim(airdrops: &mut Airdrops, campaign: u64, now: u64) -> Result<(ActorId, U256)> {
+    let expiry = airdrops
+        .campaigns
+        .get(&campaign)
+        .ok_or(Error::NonexistentCampaign)?
+        .expiry;
+
+    if now <= expiry {
+        return Err(Error::CampaignNotExpired);
+    }
+
+    let campaign = airdrops.campaigns.remove(&campaign).expect("Checked above");
+
+    Ok((campaign.funder, campaign.remaining))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alice() -> ActorId {
+        1u64.into()
+    }
+
+    fn bob() -> ActorId {
+        2u64.into()
+    }
+
+    fn charlie() -> ActorId {
+        3u64.into()
+    }
+
+    fn node(a: Hash, b: Hash) -> Hash {
+        if a <= b {
+            blake2b_256(&[a, b].concat())
+        } else {
+            blake2b_256(&[b, a].concat())
+        }
+    }
+
+    #[test]
+    fn verify() {
+        let leaves = [
+            leaf(0, alice(), 100.into()),
+            leaf(1, bob(), 200.into()),
+            leaf(2, charlie(), 300.into()),
+        ];
+        let root = node(node(leaves[0], leaves[1]), leaves[2]);
+
+        assert!(super::verify(&[leaves[1], leaves[2]], root, leaves[0]));
+        assert!(super::verify(&[leaves[0], leaves[2]], root, leaves[1]));
+        assert!(super::verify(
+            &[node(leaves[0], leaves[1])],
+            root,
+            leaves[2]
+        ));
+        assert!(!super::verify(
+            &[leaves[0], leaves[2]],
+            root,
+            leaf(1, bob(), 201.into())
+        ));
+        assert!(!super::verify(&[leaves[2]], root, leaves[0]));
+    }
+
+    #[test]
+    fn claim() {
+        let leaves = [leaf(0, alice(), 100.into()), leaf(70, bob(), 200.into())];
+        let root = node(leaves[0], leaves[1]);
+        let mut airdrops = Airdrops::default();
+
+        // # Test case #1.
+        // Campaign can't expire before it's created.
+        {
+            assert_eq!(
+                create_campaign(&mut airdrops, charlie(), root, 250.into(), 10, 10),
+                Err(Error::CampaignExpired)
+            );
+            assert_eq!(
+                create_campaign(&mut airdrops, charlie(), root, 250.into(), 20, 10),
+                Ok(1)
+            );
+        }
+
+        // # Test case #2.
+        // Errors on invalid proof and unknown campaign.
+        {
+            assert_eq!(
+                super::claim(&mut airdrops, 1, 0, alice(), 101.into(), &[leaves[1]], 11),
+                Err(Error::InvalidProof)
+            );
+            assert_eq!(
+                super::claim(&mut airdrops, 2, 0, alice(), 100.into(), &[leaves[1]], 11),
+                Err(Error::NonexistentCampaign)
+            );
+        }
+
+        // # Test case #3.
+        // Successful claim can't be repeated.
+        {
+            assert_eq!(
+                super::claim(&mut airdrops, 1, 0, alice(), 100.into(), &[leaves[1]], 11),
+                Ok(())
+            );
+            assert!(is_claimed(&airdrops, 1, 0));
+            assert!(!is_claimed(&airdrops, 1, 70));
+            assert_eq!(
+                super::claim(&mut airdrops, 1, 0, alice(), 100.into(), &[leaves[1]], 11),
+                Err(Error::AlreadyClaimed)
+            );
+            assert_eq!(airdrops.campaigns[&1].remaining, 150.into());
+        }
+
+        // # Test case #4.
+        // Claim can't exceed what's left of funding.
+        {
+            assert_eq!(
+                super::claim(&mut airdrops, 1, 70, bob(), 200.into(), &[leaves[0]], 11),
+                Err(Error::InsufficientBalance)
+            );
+            assert!(!is_claimed(&airdrops, 1, 70));
+        }
+
+        // # Test case #5.
+        // Claims stop and funds are reclaimable after expiry.
+        {
+            assert_eq!(
+                reclaim(&mut airdrops, 1, 20),
+                Err(Error::CampaignNotExpired)
+            );
+            assert_eq!(
+                super::claim(&mut airdrops, 1, 70, bob(), 200.into(), &[leaves[0]], 21),
+                Err(Error::CampaignExpired)
+            );
+            assert_eq!(reclaim(&mut airdrops, 1, 21), Ok((charlie(), 150.into())));
+            assert_eq!(
+                reclaim(&mut airdrops, 1, 21),
+                Err(Error::NonexistentCampaign)
+            );
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use super::funcs::{self, BASIS_POINTS};
+use super::utils::{Error, Result};
+use sails_rs::{collections::HashSet, prelude::*};
+
+/// Fee in basis points charged on transfers and credited to `treasury`.
+/// Transfers from, to or between exempt accounts and the treasury are free.
+#[derive(Default)]
+pub struct FeeConfig {
+    pub fee_basis_points: u16,
+    pub treasury: ActorId,
+    pub exempt: HashSet<ActorId>,
+}
+
+pub fn set_transfer_fee(
+    config: &mut FeeConfig,
+    fee_basis_points: u16,
+    treasury: ActorId,
+) -> Result<()> {
+    if fee_basis_points > BASIS_POINTS || (fee_basis_points != 0 && treasury.is_zero()) {
+        return Err(Error::InvalidFee);
+    }
+
+    config.fee_basis_points = fee_basis_points;
+    config.treasury = treasury;
+
+    Ok(())
+}
+
+/// Returns whether exemption of `account` changed.
+pub fn set_fee_exempt(config: &mut FeeConfig, account: ActorId, exempt: bool) -> bool {
+    if exempt {
+        config.exempt.insert(account)
+    } else {
+        config.exempt.remove(&account)
+    }
+}
+
+pub fn fee_of(config: &FeeConfig, from: ActorId, to: ActorId, value: U256) -> Result<U256> {
+    let exempt = [from, to]
+        .iter()
+        .any(|account| *account == config.treasury || config.exempt.contains(account));
+
+    if exempt {
+        return Ok(U256::zero());
+    }
+
+    funcs::basis_points_of(value, config.fee_basis_points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alice() -> ActorId {
+        1u64.into()
+    }
+
+    fn bob() -> ActorId {
+        2u64.into()
+    }
+
+    fn treasury() -> ActorId {
+        3u64.into()
+    }
+
+    #[test]
+    fn fee_of() {
+        let mut config = FeeConfig::default();
+
+        // # Test case #1.
+        // No fee is charged by default.
+        {
+            assert_eq!(
+                super::fee_of(&config, alice(), bob(), 1_000.into()),
+                Ok(U256::zero())
+            );
+        }
+
+        // # Test case #2.
+        // Fee must go to some treasury and can't exceed the transfer.
+        {
+            assert_eq!(
+                set_transfer_fee(&mut config, 100, ActorId::zero()),
+                Err(Error::InvalidFee)
+            );
+            assert_eq!(
+                set_transfer_fee(&mut config, 10_001, treasury()),
+                Err(Error::InvalidFee)
+            );
+            assert_eq!(set_transfer_fee(&mut config, 100, treasury()), Ok(()));
+            assert_eq!(
+                super::fee_of(&config, alice(), bob(), 1_050.into()),
+                Ok(10.into())
+            );
+        }
+
+        // # Test case #3.
+        // Transfers of the treasury and exempt accounts are free.
+        {
+            assert_eq!(
+                super::fee_of(&config, treasury(), bob(), 1_000.into()),
+                Ok(U256::zero())
+            );
+            assert_eq!(
+                super::fee_of(&config, alice(), treasury(), 1_000.into()),
+                Ok(U256::zero())
+            );
+
+            assert!(set_fee_exempt(&mut config, bob(), true));
+            assert!(!set_fee_exempt(&mut config, bob(), true));
+            assert_eq!(
+                super::fee_of(&config, alice(), bob(), 1_000.into()),
+                Ok(U256::zero())
+            );
+            assert!(set_fee_exempt(&mut config, bob(), false));
+            assert_eq!(
+                super::fee_of(&config, bob(), alice(), 1_000.into()),
+                Ok(10.into())
+            );
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use super::funcs::{self, BASIS_POINTS};
+use super::utils::{Error, Result};
+use sails_rs::{collections::HashMap, prelude::*};
+
+/// Service and method routes a program must expose to borrow with
+/// `flash_loan`, replying `true` to accept the loan.
+pub const BORROWER_SERVICE: &str = "FlashBorrower";
+pub const BORROWER_METHOD: &str = "OnFlashLoan";
+
+/// Flash loans are disabled until an admin sets a non-zero `max_loan`.
+#[derive(Default)]
+pub struct FlashLoanConfig {
+    pub fee_basis_points: u16,
+    pub max_loan: U256,
+}
+
+/// Loans not burned back yet, which snapshots and votes leave out.
+#[derive(Default)]
+pub struct Loans {
+    pub by_receiver: HashMap<ActorId, U256>,
+    pub total: U256,
+}
+
+pub fn set_config(
+    config: &mut FlashLoanConfig,
+    fee_basis_points: u16,
+    max_loan: U256,
+) -> Result<()> {
+    if fee_basis_points > BASIS_POINTS {
+        return Err(Error::InvalidFee);
+    }
+
+    config.fee_basis_points = fee_basis_points;
+    config.max_loan = max_loan;
+
+    Ok(())
+}
+
+/// Returns the most that can be lent, so that minting it neither exceeds
+/// `max_loan` nor `max_supply`.
+pub fn max_flash_loan(
+    config: &FlashLoanConfig,
+    max_supply: Option<U256>,
+    total_supply: U256,
+) -> U256 {
+    let available = max_supply
+        .map(|max_supply| max_supply.saturating_sub(total_supply))
+        .unwrap_or(U256::MAX - total_supply);
+
+    config.max_loan.min(available)
+}
+
+pub fn flash_fee(config: &FlashLoanConfig, amount: U256) -> Result<U256> {
+    funcs::basis_points_of(amount, config.fee_basis_points)
+}
+
+/// Checks `amount` can be lent, returning the fee to repay along with it.
+pub fn loan(
+    config: &FlashLoanConfig,
+    max_supply: Option<U256>,
+    total_supply: U256,
+    amount: U256,
+) -> Result<U256> {
+    if amount > max_flash_loan(config, max_supply, total_supply) {
+        return Err(Error::FlashLoanExceeded);
+    }
+
+    flash_fee(config, amount)
+}
+
+/// Records `amount` lent to `receiver` until [`repay`] is called.
+pub fn lend(loans: &mut Loans, receiver: ActorId, amount: U256) -> Result<()> {
+    let total = loans
+        .total
+        .checked_add(amount)
+        .ok_or(Error::NumericOverflow)?;
+    let loaned = loaned(loans, receiver)
+        .checked_add(amount)
+        .ok_or(Error::NumericOverflow)?;
+
+    loans.by_receiver.insert(receiver, loaned);
+    loans.total = total;
+
+    Ok(())
+}
+
+/// Forgets `amount` lent to `receiver` by [`lend`].
+pub fn repay(loans: &mut Loans, receiver: ActorId, amount: U256) {
+    let loaned = loaned(loans, receiver).saturating_sub(amount);
+    if loaned.is_zero() {
+        loans.by_receiver.remove(&receiver);
+    } else {
+        loans.by_receiver.insert(receiver, loaned);
+    }
+    loans.total = loans.total.saturating_sub(amount);
+}
+
+pub fn loaned(loans: &Loans, account: ActorId) -> U256 {
+    loans.by_receiver.get(&account).copied().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loan() {
+        let mut config = FlashLoanConfig::default();
+
+        // # Test case #1.
+        // Loans are disabled by default.
+        {
+            assert_eq!(max_flash_loan(&config, None, U256::zero()), U256::zero());
+            assert_eq!(
+                super::loan(&config, None, U256::zero(), 1.into()),
+                Err(Error::FlashLoanExceeded)
+            );
+        }
+
+        // # Test case #2.
+        // Fee can't exceed the loan.
+        {
+            assert_eq!(
+                set_config(&mut config, 10_001, 1_000.into()),
+                Err(Error::InvalidFee)
+            );
+            assert_eq!(set_config(&mut config, 30, 1_000.into()), Ok(()));
+        }
+
+        // # Test case #3.
+        // Loan is bounded by both max loan and max supply.
+        {
+            assert_eq!(max_flash_loan(&config, None, 100.into()), 1_000.into());
+            assert_eq!(
+                max_flash_loan(&config, Some(500.into()), 100.into()),
+                400.into()
+            );
+            assert_eq!(
+                super::loan(&config, Some(500.into()), 100.into(), 401.into()),
+                Err(Error::FlashLoanExceeded)
+            );
+            assert_eq!(
+                super::loan(&config, None, 100.into(), 1_000.into()),
+                Ok(3.into())
+            );
+        }
+
+        // # Test case #4.
+        // Fee is rounded down and doesn't overflow.
+        {
+            assert_eq!(flash_fee(&config, 333.into()), Ok(U256::zero()));
+            assert!(flash_fee(&config, U256::MAX).is_ok_and(|fee| fee < U256::MAX / 333));
+        }
+    }
+
+    #[test]
+    fn lend_and_repay() {
+        let mut loans = Loans::default();
+        let alice = ActorId::from(1u64);
+        let bob = ActorId::from(2u64);
+
+        // # Test case #1.
+        // Loans to the same receiver add up.
+        {
+            assert_eq!(lend(&mut loans, alice, 100.into()), Ok(()));
+            assert_eq!(lend(&mut loans, alice, 50.into()), Ok(()));
+            assert_eq!(lend(&mut loans, bob, 10.into()), Ok(()));
+            assert_eq!(loaned(&loans, alice), 150.into());
+            assert_eq!(loans.total, 160.into());
+        }
+
+        // # Test case #2.
+        // Repaid loans are forgotten.
+        {
+            repay(&mut loans, alice, 100.into());
+            assert_eq!(loaned(&loans, alice), 50.into());
+            repay(&mut loans, alice, 50.into());
+            assert!(!loans.by_receiver.contains_key(&alice));
+            assert_eq!(loans.total, 10.into());
+        }
+
+        // # Test case #3.
+        // Total can't overflow.
+        {
+            assert_eq!(
+                lend(&mut loans, alice, U256::MAX),
+                Err(Error::NumericOverflow)
+            );
+            assert_eq!(loaned(&loans, alice), U256::zero());
+        }
+    }
+}
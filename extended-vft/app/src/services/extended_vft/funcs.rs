@@ -1,12 +1,11 @@
+use super::utils::{Error, Result};
 use sails_rs::prelude::*;
-use vft_service::{
-    funcs,
-    utils::{Error, Result, *},
-};
+use vft_service::{funcs, utils::*};
 
 pub fn mint(
     balances: &mut BalancesMap,
     total_supply: &mut U256,
+    max_supply: Option<U256>,
     to: ActorId,
     value: U256,
 ) -> Result<bool> {
@@ -18,6 +17,10 @@ pub fn mint(
         .checked_add(value)
         .ok_or(Error::NumericOverflow)?;
 
+    if max_supply.is_some_and(|max_supply| new_total_supply > max_supply) {
+        return Err(Error::MaxSupplyExceeded);
+    }
+
     let new_to = funcs::balance_of(balances, to)
         .checked_add(value)
         .ok_or(Error::NumericOverflow)?;
@@ -52,3 +55,223 @@ pub fn burn(
     *total_supply = new_total_supply;
     Ok(true)
 }
+
+/// Burns `value` of `from` on behalf of `spender`, consuming its allowance
+/// unless `spender` is `from` itself.
+pub fn burn_from(
+    allowances: &mut AllowancesMap,
+    balances: &mut BalancesMap,
+    total_supply: &mut U256,
+    spender: ActorId,
+    from: ActorId,
+    value: U256,
+) -> Result<bool> {
+    if spender == from {
+        return burn(balances, total_supply, from, value);
+    }
+
+    if value.is_zero() {
+        return Ok(false);
+    }
+
+    let new_allowance = funcs::allowance(allowances, from, spender)
+        .checked_sub(value)
+        .ok_or(Error::InsufficientAllowance)?;
+
+    let _res = burn(balances, total_supply, from, value)?;
+    debug_assert!(_res);
+
+    funcs::approve(allowances, from, spender, new_allowance);
+
+    Ok(true)
+}
+
+/// Sets a new cap on total supply, which can only be lowered and never below
+/// current total supply.
+pub fn set_max_supply(
+    max_supply: &mut Option<U256>,
+    total_supply: U256,
+    new_max_supply: U256,
+) -> Result<bool> {
+    if *max_supply == Some(new_max_supply) {
+        return Ok(false);
+    }
+
+    if max_supply.is_some_and(|max_supply| new_max_supply > max_supply)
+        || new_max_supply < total_supply
+    {
+        return Err(Error::InvalidMaxSupply);
+    }
+
+    *max_supply = Some(new_max_supply);
+
+    Ok(true)
+}
+
+pub const BASIS_POINTS: u16 = 10_000;
+
+/// Returns `basis_points` of `value`, rounded down.
+pub fn basis_points_of(value: U256, basis_points: u16) -> Result<U256> {
+    let part = value.full_mul(basis_points.into()) / BASIS_POINTS;
+
+    part.try_into().map_err(|_| Error::NumericOverflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alice() -> ActorId {
+        1u64.into()
+    }
+
+    fn bob() -> ActorId {
+        2u64.into()
+    }
+
+    #[test]
+    fn mint() {
+        let mut balances = BalancesMap::new();
+        let mut total_supply = U256::zero();
+        let max_supply = Some(U256::from(100));
+
+        // # Test case #1.
+        // Successful mint up to max supply.
+        {
+            assert_eq!(
+                super::mint(
+                    &mut balances,
+                    &mut total_supply,
+                    max_supply,
+                    alice(),
+                    100.into()
+                ),
+                Ok(true)
+            );
+            assert_eq!(total_supply, 100.into());
+        }
+
+        // # Test case #2.
+        // Error on exceeding max supply.
+        {
+            assert_eq!(
+                super::mint(
+                    &mut balances,
+                    &mut total_supply,
+                    max_supply,
+                    alice(),
+                    1.into()
+                ),
+                Err(Error::MaxSupplyExceeded)
+            );
+            assert_eq!(total_supply, 100.into());
+        }
+
+        // # Test case #3.
+        // No cap without max supply.
+        {
+            assert_eq!(
+                super::mint(&mut balances, &mut total_supply, None, alice(), 1.into()),
+                Ok(true)
+            );
+            assert_eq!(funcs::balance_of(&balances, alice()), 101.into());
+        }
+    }
+
+    #[test]
+    fn burn_from() {
+        let mut allowances: AllowancesMap = [((alice(), bob()), U256::from(30))].into();
+        let mut balances: BalancesMap = [(alice(), U256::from(100))].into();
+        let mut total_supply = U256::from(100);
+
+        // # Test case #1.
+        // Error on burning more than allowed.
+        {
+            assert_eq!(
+                super::burn_from(
+                    &mut allowances,
+                    &mut balances,
+                    &mut total_supply,
+                    bob(),
+                    alice(),
+                    31.into()
+                ),
+                Err(Error::InsufficientAllowance)
+            );
+        }
+
+        // # Test case #2.
+        // Successful burn consumes allowance.
+        {
+            assert_eq!(
+                super::burn_from(
+                    &mut allowances,
+                    &mut balances,
+                    &mut total_supply,
+                    bob(),
+                    alice(),
+                    30.into()
+                ),
+                Ok(true)
+            );
+            assert_eq!(funcs::allowance(&allowances, alice(), bob()), U256::zero());
+            assert_eq!(funcs::balance_of(&balances, alice()), 70.into());
+            assert_eq!(total_supply, 70.into());
+        }
+
+        // # Test case #3.
+        // Owner burns without allowance.
+        {
+            assert_eq!(
+                super::burn_from(
+                    &mut allowances,
+                    &mut balances,
+                    &mut total_supply,
+                    alice(),
+                    alice(),
+                    70.into()
+                ),
+                Ok(true)
+            );
+            assert!(balances.is_empty());
+            assert_eq!(total_supply, U256::zero());
+        }
+    }
+
+    #[test]
+    fn set_max_supply() {
+        let mut max_supply = None;
+
+        // # Test case #1.
+        // Cap can be introduced and lowered down to total supply.
+        {
+            assert_eq!(
+                super::set_max_supply(&mut max_supply, 50.into(), 100.into()),
+                Ok(true)
+            );
+            assert_eq!(
+                super::set_max_supply(&mut max_supply, 50.into(), 100.into()),
+                Ok(false)
+            );
+            assert_eq!(
+                super::set_max_supply(&mut max_supply, 50.into(), 50.into()),
+                Ok(true)
+            );
+            assert_eq!(max_supply, Some(50.into()));
+        }
+
+        // # Test case #2.
+        // Error on raising cap or lowering it below total supply.
+        {
+            assert_eq!(
+                super::set_max_supply(&mut max_supply, 50.into(), 60.into()),
+                Err(Error::InvalidMaxSupply)
+            );
+            assert_eq!(
+                super::set_max_supply(&mut max_supply, 50.into(), 40.into()),
+                Err(Error::InvalidMaxSupply)
+            );
+            assert_eq!(max_supply, Some(50.into()));
+        }
+    }
+}
@@ -1,18 +1,45 @@
 #![allow(static_mut_refs)]
 use sails_rs::{
     collections::HashSet,
-    gstd::{msg, service},
+    gstd::{exec, msg, service, services::Exposure},
     prelude::*,
 };
+mod airdrop;
+mod fees;
+mod flash;
 mod funcs;
+mod quotas;
+mod snapshots;
+pub mod utils;
+mod vesting;
+mod votes;
 use crate::services;
-use vft_service::{Service as VftService, Storage};
+use airdrop::Airdrops;
+use fees::FeeConfig;
+use flash::FlashLoanConfig;
+use quotas::QuotasMap;
+use snapshots::Snapshots;
+use vesting::Vesting;
+use vft_service::{checked, Hooks, Service as VftService, Storage};
+use votes::Votes;
 
 #[derive(Default)]
 pub struct ExtendedStorage {
     minters: HashSet<ActorId>,
     burners: HashSet<ActorId>,
     admins: HashSet<ActorId>,
+    compliance: HashSet<ActorId>,
+    frozen: HashSet<ActorId>,
+    paused: bool,
+    max_supply: Option<U256>,
+    flash_loan: FlashLoanConfig,
+    flash_loans: flash::Loans,
+    fees: FeeConfig,
+    minter_quotas: QuotasMap,
+    airdrops: Airdrops,
+    vesting: Vesting,
+    snapshots: Snapshots,
+    votes: Votes,
 }
 
 static mut EXTENDED_STORAGE: Option<ExtendedStorage> = None;
@@ -22,8 +49,115 @@ static mut EXTENDED_STORAGE: Option<ExtendedStorage> = None;
 #[codec(crate = sails_rs::scale_codec)]
 #[scale_info(crate = sails_rs::scale_info)]
 pub enum Event {
-    Minted { to: ActorId, value: U256 },
-    Burned { from: ActorId, value: U256 },
+    Minted {
+        to: ActorId,
+        value: U256,
+    },
+    Burned {
+        from: ActorId,
+        value: U256,
+    },
+    Snapshot {
+        id: u64,
+    },
+    DelegateChanged {
+        delegator: ActorId,
+        from_delegate: ActorId,
+        to_delegate: ActorId,
+    },
+    DelegateVotesChanged {
+        delegate: ActorId,
+        previous_votes: U256,
+        new_votes: U256,
+    },
+    Paused,
+    Unpaused,
+    MaxSupplyChanged {
+        max_supply: U256,
+    },
+    TransferFeeSet {
+        fee_basis_points: u16,
+        treasury: ActorId,
+    },
+    FeeExemptionSet {
+        account: ActorId,
+        exempt: bool,
+    },
+    FeeCharged {
+        from: ActorId,
+        to: ActorId,
+        treasury: ActorId,
+        fee: U256,
+    },
+    FlashLoanConfigSet {
+        fee_basis_points: u16,
+        max_loan: U256,
+    },
+    FlashLoan {
+        receiver: ActorId,
+        initiator: ActorId,
+        amount: U256,
+        fee: U256,
+    },
+    MinterAllowanceSet {
+        minter: ActorId,
+        amount: U256,
+        epoch_blocks: u32,
+    },
+    MinterAllowanceUsed {
+        minter: ActorId,
+        remaining: U256,
+    },
+    MinterAllowanceRemoved {
+        minter: ActorId,
+    },
+    Frozen {
+        account: ActorId,
+    },
+    Unfrozen {
+        account: ActorId,
+    },
+    ForcedTransfer {
+        from: ActorId,
+        to: ActorId,
+        value: U256,
+    },
+    CampaignCreated {
+        campaign: u64,
+        root: [u8; 32],
+        amount: U256,
+        expiry: u64,
+    },
+    Claimed {
+        campaign: u64,
+        index: u32,
+        account: ActorId,
+        amount: U256,
+    },
+    Reclaimed {
+        campaign: u64,
+        funder: ActorId,
+        amount: U256,
+    },
+    VestingCreated {
+        schedule: u64,
+        beneficiary: ActorId,
+        total: U256,
+        start: u64,
+        cliff: u64,
+        duration: u64,
+        revocable: bool,
+    },
+    VestingReleased {
+        schedule: u64,
+        beneficiary: ActorId,
+        amount: U256,
+    },
+    VestingRevoked {
+        schedule: u64,
+        funder: ActorId,
+        amount: U256,
+    },
 }
 #[derive(Clone)]
 pub struct ExtendedService {
@@ -37,17 +171,27 @@ impl ExtendedService {
         }
     }
 
-    pub fn init(name: String, symbol: String, decimals: u8) -> Self {
+    pub fn init(name: String, symbol: String, decimals: u8, max_supply: Option<U256>) -> Self {
         let admin = msg::source();
         unsafe {
             EXTENDED_STORAGE = Some(ExtendedStorage {
                 admins: [admin].into(),
                 minters: [admin].into(),
                 burners: [admin].into(),
+                compliance: [admin].into(),
+                max_supply,
+                ..Default::default()
             });
         };
         ExtendedService {
-            vft: <VftService>::init(name, symbol, decimals),
+            vft: <VftService>::init(name, symbol, decimals).with_hooks(&ExtendedHooks),
+        }
+    }
+
+    /// Makes the inherited methods panic on errors instead of returning them.
+    pub fn with_panicking_errors(self) -> Self {
+        ExtendedService {
+            vft: self.vft.with_panicking_errors(),
         }
     }
 
@@ -76,37 +220,762 @@ impl From<ExtendedService> for VftService {
 #[service(extends = VftService, events = Event)]
 impl ExtendedService {
     #[export]
-    pub fn mint(&mut self, to: ActorId, value: U256) -> bool {
+    pub fn mint(&mut self, to: ActorId, value: U256) -> utils::Result<bool> {
         if !self.get().minters.contains(&msg::source()) {
             panic!("Not allowed to mint")
         };
+        self.ensure_not_paused();
 
-        let mutated = services::utils::panicking(|| {
-            funcs::mint(Storage::balances(), Storage::total_supply(), to, value)
-        });
+        let minter = msg::source();
+        self.record_snapshot(to);
+
+        let mutated = checked(|| {
+            funcs::mint(
+                Storage::balances(),
+                Storage::total_supply(),
+                self.get().max_supply,
+                to,
+                value,
+            )
+        })?;
+        let remaining = match checked(|| {
+            quotas::spend_minter_allowance(
+                &mut self.get_mut().minter_quotas,
+                minter,
+                value,
+                exec::block_height(),
+            )
+        }) {
+            Ok(remaining) => remaining,
+            Err(err) => {
+                funcs::burn(Storage::balances(), Storage::total_supply(), to, value)
+                    .expect("Minted above");
+                return Err(err);
+            }
+        };
         if mutated {
+            if let Some(remaining) = remaining {
+                self.emit_event(Event::MinterAllowanceUsed { minter, remaining })
+                    .expect("Notification Error");
+            }
+            for event in move_votes(ActorId::zero(), to, value) {
+                self.emit_event(event).expect("Notification Error");
+            }
             self.emit_event(Event::Minted { to, value })
                 .expect("Notification Error");
         }
-        mutated
+        Ok(mutated)
     }
 
     #[export]
-    pub fn burn(&mut self, from: ActorId, value: U256) -> bool {
+    pub fn burn(&mut self, from: ActorId, value: U256) -> utils::Result<bool> {
         if !self.get().burners.contains(&msg::source()) {
             panic!("Not allowed to burn")
         };
+        self.ensure_not_paused();
+        ensure_not_escrow(from);
 
-        let mutated = services::utils::panicking(|| {
-            funcs::burn(Storage::balances(), Storage::total_supply(), from, value)
-        });
+        self.record_snapshot(from);
+
+        let mutated =
+            checked(|| funcs::burn(Storage::balances(), Storage::total_supply(), from, value))?;
+        if mutated {
+            for event in move_votes(from, ActorId::zero(), value) {
+                self.emit_event(event).expect("Notification Error");
+            }
+            self.emit_event(Event::Burned { from, value })
+                .expect("Notification Error");
+        }
+        Ok(mutated)
+    }
+
+    /// Burns `value` of the caller's own balance.
+    #[export]
+    pub fn burn_self(&mut self, value: U256) -> utils::Result<bool> {
+        let from = msg::source();
+        self.ensure_not_paused();
+        self.ensure_not_frozen(from);
+
+        self.record_snapshot(from);
+
+        let mutated =
+            checked(|| funcs::burn(Storage::balances(), Storage::total_supply(), from, value))?;
+        if mutated {
+            for event in move_votes(from, ActorId::zero(), value) {
+                self.emit_event(event).expect("Notification Error");
+            }
+            self.emit_event(Event::Burned { from, value })
+                .expect("Notification Error");
+        }
+        Ok(mutated)
+    }
+
+    /// Burns `value` of `from`, consuming the caller's allowance.
+    #[export]
+    pub fn burn_from(&mut self, from: ActorId, value: U256) -> utils::Result<bool> {
+        let spender = msg::source();
+        self.ensure_not_paused();
+        self.ensure_not_frozen(from);
+
+        self.record_snapshot(from);
+
+        let mutated = checked(|| {
+            funcs::burn_from(
+                Storage::allowances(),
+                Storage::balances(),
+                Storage::total_supply(),
+                spender,
+                from,
+                value,
+            )
+        })?;
         if mutated {
+            if spender != from {
+                let value = vft_service::funcs::allowance(Storage::allowances(), from, spender);
+                EventEmitter::<vft_service::Event>::new(self.route())
+                    .emit_event(vft_service::Event::Approval {
+                        owner: from,
+                        spender,
+                        value,
+                    })
+                    .expect("Notification Error");
+            }
+            for event in move_votes(from, ActorId::zero(), value) {
+                self.emit_event(event).expect("Notification Error");
+            }
             self.emit_event(Event::Burned { from, value })
                 .expect("Notification Error");
         }
+        Ok(mutated)
+    }
+
+    /// Limits minting of `minter` to `amount` per `epoch_blocks` blocks,
+    /// with zero `epoch_blocks` the allowance is never refilled.
+    #[export]
+    pub fn set_minter_allowance(&mut self, minter: ActorId, amount: U256, epoch_blocks: u32) {
+        self.ensure_is_admin();
+
+        quotas::set_minter_allowance(
+            &mut self.get_mut().minter_quotas,
+            minter,
+            amount,
+            epoch_blocks,
+            exec::block_height(),
+        );
+        self.emit_event(Event::MinterAllowanceSet {
+            minter,
+            amount,
+            epoch_blocks,
+        })
+        .expect("Notification Error");
+    }
+
+    /// Lifts the limit set on minting of `minter` by `set_minter_allowance`.
+    /// A zero allowance, by contrast, keeps `minter` from minting at all.
+    #[export]
+    pub fn remove_minter_allowance(&mut self, minter: ActorId) -> bool {
+        self.ensure_is_admin();
+
+        let mutated = quotas::remove_minter_allowance(&mut self.get_mut().minter_quotas, minter);
+        if mutated {
+            self.emit_event(Event::MinterAllowanceRemoved { minter })
+                .expect("Notification Error");
+        }
+        mutated
+    }
+
+    /// Returns what `minter` may still mint in the current epoch, `None` if
+    /// it isn't limited.
+    #[export]
+    pub fn remaining_minter_allowance(&self, minter: ActorId) -> Option<U256> {
+        quotas::remaining_minter_allowance(&self.get().minter_quotas, minter, exec::block_height())
+    }
+
+    #[export]
+    pub fn set_max_supply(&mut self, max_supply: U256) -> utils::Result<bool> {
+        self.ensure_is_admin();
+
+        let mutated = checked(|| {
+            funcs::set_max_supply(
+                &mut self.get_mut().max_supply,
+                *Storage::total_supply(),
+                max_supply,
+            )
+        })?;
+        if mutated {
+            self.emit_event(Event::MaxSupplyChanged { max_supply })
+                .expect("Notification Error");
+        }
+        Ok(mutated)
+    }
+
+    #[export]
+    pub fn max_supply(&self) -> Option<U256> {
+        self.get().max_supply
+    }
+
+    /// Charges `fee_basis_points` of every transfer, be it batched, held or
+    /// called, to the recipient in favor of `treasury`, zero disabling the fee.
+    #[export]
+    pub fn set_transfer_fee(
+        &mut self,
+        fee_basis_points: u16,
+        treasury: ActorId,
+    ) -> utils::Result<()> {
+        self.ensure_is_admin();
+
+        checked(|| fees::set_transfer_fee(&mut self.get_mut().fees, fee_basis_points, treasury))?;
+        self.emit_event(Event::TransferFeeSet {
+            fee_basis_points,
+            treasury,
+        })
+        .expect("Notification Error");
+
+        Ok(())
+    }
+
+    /// Exempts transfers from and to `account` from the fee, or stops doing so.
+    #[export]
+    pub fn set_fee_exempt(&mut self, account: ActorId, exempt: bool) -> bool {
+        self.ensure_is_admin();
+
+        let mutated = fees::set_fee_exempt(&mut self.get_mut().fees, account, exempt);
+        if mutated {
+            self.emit_event(Event::FeeExemptionSet { account, exempt })
+                .expect("Notification Error");
+        }
+        mutated
+    }
+
+    /// Returns the fee in basis points and the treasury it's credited to.
+    #[export]
+    pub fn transfer_fee(&self) -> (u16, ActorId) {
+        let fees = &self.get().fees;
+        (fees.fee_basis_points, fees.treasury)
+    }
+
+    #[export]
+    pub fn is_fee_exempt(&self, account: ActorId) -> bool {
+        self.get().fees.exempt.contains(&account)
+    }
+
+    /// Sets the fee of flash loans in basis points of the loan and the most
+    /// that can be lent at once, zero disabling them.
+    #[export]
+    pub fn set_flash_loan_config(
+        &mut self,
+        fee_basis_points: u16,
+        max_loan: U256,
+    ) -> utils::Result<()> {
+        self.ensure_is_admin();
+
+        checked(|| flash::set_config(&mut self.get_mut().flash_loan, fee_basis_points, max_loan))?;
+        self.emit_event(Event::FlashLoanConfigSet {
+            fee_basis_points,
+            max_loan,
+        })
+        .expect("Notification Error");
+
+        Ok(())
+    }
+
+    #[export]
+    pub fn max_flash_loan(&self) -> U256 {
+        let storage = self.get();
+        flash::max_flash_loan(
+            &storage.flash_loan,
+            storage.max_supply,
+            *Storage::total_supply(),
+        )
+    }
+
+    #[export]
+    pub fn flash_fee(&self, amount: U256) -> U256 {
+        services::utils::panicking(|| flash::flash_fee(&self.get().flash_loan, amount))
+    }
+
+    /// Burns `fee` out of the caller's spendable balance, then mints `amount`
+    /// to the `receiver` program on hold and calls its
+    /// `FlashBorrower/OnFlashLoan(initiator, amount, fee, data)` hook,
+    /// returning whether it replied `true`.
+    ///
+    /// Unlike ERC-3156 the loan isn't atomic, the receiver's reply coming in
+    /// a later message. So the loan is held until the reply and then always
+    /// burned back, and the fee is paid whatever the receiver replies. While
+    /// held, the loan is counted by `balance_of` but left out of snapshots
+    /// and votes.
+    #[export]
+    pub async fn flash_loan(
+        &mut self,
+        receiver: ActorId,
+        amount: U256,
+        data: Vec<u8>,
+    ) -> utils::Result<bool> {
+        self.ensure_not_paused();
+        self.ensure_not_frozen(receiver);
+        let initiator = msg::source();
+        self.ensure_not_frozen(initiator);
+
+        let storage = self.get();
+        let fee = checked(|| {
+            flash::loan(
+                &storage.flash_loan,
+                storage.max_supply,
+                *Storage::total_supply(),
+                amount,
+            )
+        })?;
+
+        self.record_snapshot(initiator);
+        let burned =
+            checked(|| funcs::burn(Storage::balances(), Storage::total_supply(), initiator, fee))?;
+        if burned {
+            for event in move_votes(initiator, ActorId::zero(), fee) {
+                self.emit_event(event).expect("Notification Error");
+            }
+        }
+
+        // Snapshots and votes don't see the loan, so nothing is recorded for
+        // it. Neither fails, the loan being within max supply.
+        funcs::mint(
+            Storage::held(),
+            Storage::total_supply(),
+            self.get().max_supply,
+            receiver,
+            amount,
+        )
+        .expect("Loan is checked");
+        flash::lend(&mut self.get_mut().flash_loans, receiver, amount).expect("Loan is checked");
+
+        let payload = (
+            flash::BORROWER_SERVICE,
+            flash::BORROWER_METHOD,
+            initiator,
+            amount,
+            fee,
+            data,
+        )
+            .encode();
+
+        let accepted = match msg::send_bytes_for_reply(receiver, payload, 0, 0) {
+            Ok(reply) => reply
+                .await
+                .ok()
+                .and_then(|reply| <(String, String, bool)>::decode(&mut reply.as_slice()).ok())
+                .is_some_and(|(service, method, accepted)| {
+                    service == flash::BORROWER_SERVICE
+                        && method == flash::BORROWER_METHOD
+                        && accepted
+                }),
+            Err(_) => false,
+        };
+
+        flash::repay(&mut self.get_mut().flash_loans, receiver, amount);
+        // Held value is never moved by anyone but this method, so the loan is
+        // still there to burn.
+        funcs::burn(Storage::held(), Storage::total_supply(), receiver, amount)
+            .expect("Loan is held");
+
+        self.emit_event(Event::FlashLoan {
+            receiver,
+            initiator,
+            amount,
+            fee,
+        })
+        .expect("Notification Error");
+
+        Ok(accepted)
+    }
+
+    #[export]
+    pub fn snapshot(&mut self) -> utils::Result<u64> {
+        self.ensure_is_admin();
+
+        let id = checked(|| snapshots::snapshot(&mut self.get_mut().snapshots))?;
+        self.emit_event(Event::Snapshot { id })
+            .expect("Notification Error");
+        Ok(id)
+    }
+
+    #[export]
+    pub fn balance_of_at(&self, account: ActorId, snapshot_id: u64) -> U256 {
+        services::utils::panicking(|| {
+            snapshots::balance_of_at(
+                &self.get().snapshots,
+                account,
+                voting_balance(account),
+                snapshot_id,
+            )
+        })
+    }
+
+    #[export]
+    pub fn total_supply_at(&self, snapshot_id: u64) -> U256 {
+        services::utils::panicking(|| {
+            snapshots::total_supply_at(&self.get().snapshots, voting_total_supply(), snapshot_id)
+        })
+    }
+
+    #[export]
+    pub fn delegate(&mut self, to: ActorId) {
+        let delegator = msg::source();
+        let from = votes::delegate(&mut self.get_mut().votes, delegator, to);
+        self.emit_event(Event::DelegateChanged {
+            delegator,
+            from_delegate: from,
+            to_delegate: to,
+        })
+        .expect("Notification Error");
+
+        let balance = voting_balance(delegator);
+        let changes = services::utils::panicking(|| {
+            votes::move_voting_power(
+                &mut self.get_mut().votes,
+                from,
+                to,
+                balance,
+                exec::block_height(),
+            )
+        });
+        for event in votes_changed(changes) {
+            self.emit_event(event).expect("Notification Error");
+        }
+    }
+
+    #[export]
+    pub fn delegates(&self, account: ActorId) -> ActorId {
+        votes::delegates(&self.get().votes, account)
+    }
+
+    #[export]
+    pub fn get_votes(&self, account: ActorId) -> U256 {
+        votes::get_votes(&self.get().votes, account)
+    }
+
+    #[export]
+    pub fn get_past_votes(&self, account: ActorId, block: u32) -> U256 {
+        services::utils::panicking(|| {
+            votes::get_past_votes(&self.get().votes, account, block, exec::block_height())
+        })
+    }
+
+    #[export]
+    pub fn pause(&mut self) -> bool {
+        self.ensure_is_admin();
+
+        let mutated = !self.get().paused;
+        if mutated {
+            self.get_mut().paused = true;
+            self.emit_event(Event::Paused).expect("Notification Error");
+        }
+        mutated
+    }
+
+    #[export]
+    pub fn unpause(&mut self) -> bool {
+        self.ensure_is_admin();
+
+        let mutated = self.get().paused;
+        if mutated {
+            self.get_mut().paused = false;
+            self.emit_event(Event::Unpaused)
+                .expect("Notification Error");
+        }
+        mutated
+    }
+
+    #[export]
+    pub fn paused(&self) -> bool {
+        self.get().paused
+    }
+
+    /// Blocks `account` from sending and receiving transfers, except for the
+    /// value already escrowed by `transfer_and_call`, which is still settled.
+    #[export]
+    pub fn freeze(&mut self, account: ActorId) -> bool {
+        self.ensure_is_compliance();
+        ensure_not_escrow(account);
+
+        let mutated = self.get_mut().frozen.insert(account);
+        if mutated {
+            self.emit_event(Event::Frozen { account })
+                .expect("Notification Error");
+        }
+        mutated
+    }
+
+    #[export]
+    pub fn unfreeze(&mut self, account: ActorId) -> bool {
+        self.ensure_is_compliance();
+
+        let mutated = self.get_mut().frozen.remove(&account);
+        if mutated {
+            self.emit_event(Event::Unfrozen { account })
+                .expect("Notification Error");
+        }
         mutated
     }
 
+    #[export]
+    pub fn is_frozen(&self, account: ActorId) -> bool {
+        self.get().frozen.contains(&account)
+    }
+
+    /// Moves `value` from `from` to `to` regardless of allowances and
+    /// frozen accounts, `from` being any account but the program's own.
+    #[export]
+    pub fn force_transfer(
+        &mut self,
+        from: ActorId,
+        to: ActorId,
+        value: U256,
+    ) -> utils::Result<bool> {
+        self.ensure_is_compliance();
+        ensure_not_escrow(from);
+
+        let (mutated, events) = checked(|| internal_transfer(from, to, value))?;
+        if mutated {
+            for event in events {
+                self.emit_event(event).expect("Notification Error");
+            }
+            self.emit_event(Event::ForcedTransfer { from, to, value })
+                .expect("Notification Error");
+        }
+        Ok(mutated)
+    }
+
+    /// Escrows `amount` of the caller's tokens for an airdrop to the accounts
+    /// in the Merkle tree with `root`, claimable until `expiry` (block
+    /// timestamp in ms). Leaves are `blake2b_256((index: u32, account, amount).encode())`
+    /// and pairs are hashed sorted.
+    #[export]
+    pub fn create_campaign(
+        &mut self,
+        root: [u8; 32],
+        amount: U256,
+        expiry: u64,
+    ) -> utils::Result<u64> {
+        self.ensure_is_admin();
+        self.ensure_not_paused();
+        let funder = msg::source();
+
+        let (_, events) = checked(|| internal_transfer(funder, exec::program_id(), amount))?;
+        let campaign = checked(|| {
+            airdrop::create_campaign(
+                &mut self.get_mut().airdrops,
+                funder,
+                root,
+                amount,
+                expiry,
+                exec::block_timestamp(),
+            )
+        })
+        .inspect_err(|_| revert_escrow(funder, amount))?;
+        for event in events {
+            self.emit_event(event).expect("Notification Error");
+        }
+        self.emit_event(Event::CampaignCreated {
+            campaign,
+            root,
+            amount,
+            expiry,
+        })
+        .expect("Notification Error");
+
+        Ok(campaign)
+    }
+
+    /// Transfers `amount` to the caller from the escrow of `campaign`.
+    #[export]
+    pub fn claim(
+        &mut self,
+        campaign: u64,
+        index: u32,
+        amount: U256,
+        proof: Vec<[u8; 32]>,
+    ) -> utils::Result<()> {
+        self.ensure_not_paused();
+        let account = msg::source();
+        self.ensure_not_frozen(account);
+
+        checked(|| {
+            airdrop::claim(
+                &mut self.get_mut().airdrops,
+                campaign,
+                index,
+                account,
+                amount,
+                &proof,
+                exec::block_timestamp(),
+            )
+        })?;
+        let (_, events) = escrow_payout(account, amount);
+        for event in events {
+            self.emit_event(event).expect("Notification Error");
+        }
+        self.emit_event(Event::Claimed {
+            campaign,
+            index,
+            account,
+            amount,
+        })
+        .expect("Notification Error");
+
+        Ok(())
+    }
+
+    /// Returns unclaimed tokens of an expired `campaign` to its funder.
+    #[export]
+    pub fn reclaim(&mut self, campaign: u64) -> utils::Result<U256> {
+        self.ensure_is_admin();
+
+        let (funder, amount) = checked(|| {
+            airdrop::reclaim(
+                &mut self.get_mut().airdrops,
+                campaign,
+                exec::block_timestamp(),
+            )
+        })?;
+        let (_, events) = escrow_payout(funder, amount);
+        for event in events {
+            self.emit_event(event).expect("Notification Error");
+        }
+        self.emit_event(Event::Reclaimed {
+            campaign,
+            funder,
+            amount,
+        })
+        .expect("Notification Error");
+
+        Ok(amount)
+    }
+
+    #[export]
+    pub fn is_claimed(&self, campaign: u64, index: u32) -> bool {
+        airdrop::is_claimed(&self.get().airdrops, campaign, index)
+    }
+
+    /// Escrows `total` of the caller's tokens to vest to `beneficiary`
+    /// linearly over `duration` ms from `start` (block timestamp in ms),
+    /// with nothing vested until `cliff` ms pass.
+    #[export]
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_vesting(
+        &mut self,
+        beneficiary: ActorId,
+        total: U256,
+        start: u64,
+        cliff: u64,
+        duration: u64,
+        revocable: bool,
+    ) -> utils::Result<u64> {
+        self.ensure_is_admin();
+        self.ensure_not_paused();
+        let funder = msg::source();
+
+        let (_, events) = checked(|| internal_transfer(funder, exec::program_id(), total))?;
+        let schedule = checked(|| {
+            vesting::create_schedule(
+                &mut self.get_mut().vesting,
+                funder,
+                beneficiary,
+                total,
+                start,
+                cliff,
+                duration,
+                revocable,
+            )
+        })
+        .inspect_err(|_| revert_escrow(funder, total))?;
+        for event in events {
+            self.emit_event(event).expect("Notification Error");
+        }
+        self.emit_event(Event::VestingCreated {
+            schedule,
+            beneficiary,
+            total,
+            start,
+            cliff,
+            duration,
+            revocable,
+        })
+        .expect("Notification Error");
+
+        Ok(schedule)
+    }
+
+    /// Transfers tokens vested so far and not yet released to the
+    /// beneficiary of `schedule`.
+    #[export]
+    pub fn release(&mut self, schedule: u64) -> utils::Result<U256> {
+        self.ensure_not_paused();
+
+        let (beneficiary, amount) = checked(|| {
+            vesting::release(
+                &mut self.get_mut().vesting,
+                schedule,
+                exec::block_timestamp(),
+            )
+        })?;
+        if amount.is_zero() {
+            return Ok(amount);
+        }
+        self.ensure_not_frozen(beneficiary);
+
+        let (_, events) = escrow_payout(beneficiary, amount);
+        for event in events {
+            self.emit_event(event).expect("Notification Error");
+        }
+        self.emit_event(Event::VestingReleased {
+            schedule,
+            beneficiary,
+            amount,
+        })
+        .expect("Notification Error");
+
+        Ok(amount)
+    }
+
+    /// Returns unvested tokens of a revocable `schedule` to its funder.
+    #[export]
+    pub fn revoke_vesting(&mut self, schedule: u64) -> utils::Result<U256> {
+        self.ensure_is_admin();
+
+        let (funder, amount) = checked(|| {
+            vesting::revoke(
+                &mut self.get_mut().vesting,
+                schedule,
+                exec::block_timestamp(),
+            )
+        })?;
+        let (_, events) = escrow_payout(funder, amount);
+        for event in events {
+            self.emit_event(event).expect("Notification Error");
+        }
+        self.emit_event(Event::VestingRevoked {
+            schedule,
+            funder,
+            amount,
+        })
+        .expect("Notification Error");
+
+        Ok(amount)
+    }
+
+    #[export]
+    pub fn vested_amount(&self, schedule: u64) -> U256 {
+        services::utils::panicking(|| {
+            vesting::vested_amount(&self.get().vesting, schedule, exec::block_timestamp())
+        })
+    }
+
+    #[export]
+    pub fn releasable_amount(&self, schedule: u64) -> U256 {
+        services::utils::panicking(|| {
+            vesting::releasable_amount(&self.get().vesting, schedule, exec::block_timestamp())
+        })
+    }
+
     #[export]
     pub fn grant_admin_role(&mut self, to: ActorId) {
         self.ensure_is_admin();
@@ -125,6 +994,12 @@ impl ExtendedService {
         self.get_mut().burners.insert(to);
     }
 
+    #[export]
+    pub fn grant_compliance_role(&mut self, to: ActorId) {
+        self.ensure_is_admin();
+        self.get_mut().compliance.insert(to);
+    }
+
     #[export]
     pub fn revoke_admin_role(&mut self, from: ActorId) {
         self.ensure_is_admin();
@@ -143,6 +1018,12 @@ impl ExtendedService {
         self.get_mut().burners.remove(&from);
     }
 
+    #[export]
+    pub fn revoke_compliance_role(&mut self, from: ActorId) {
+        self.ensure_is_admin();
+        self.get_mut().compliance.remove(&from);
+    }
+
     #[export]
     pub fn minters(&self) -> Vec<ActorId> {
         self.get().minters.clone().into_iter().collect()
@@ -157,6 +1038,11 @@ impl ExtendedService {
     pub fn admins(&self) -> Vec<ActorId> {
         self.get().admins.clone().into_iter().collect()
     }
+
+    #[export]
+    pub fn compliance(&self) -> Vec<ActorId> {
+        self.get().compliance.clone().into_iter().collect()
+    }
 }
 
 impl ExtendedService {
@@ -165,4 +1051,174 @@ impl ExtendedService {
             panic!("Not admin")
         };
     }
+
+    fn ensure_is_compliance(&self) {
+        if !self.get().compliance.contains(&msg::source()) {
+            panic!("Not compliance")
+        };
+    }
+
+    fn ensure_not_frozen(&self, account: ActorId) {
+        if self.get().frozen.contains(&account) {
+            panic!("Account is frozen")
+        };
+    }
+
+    fn ensure_not_paused(&self) {
+        if self.get().paused {
+            panic!("Paused")
+        };
+    }
+
+    fn record_snapshot(&mut self, account: ActorId) {
+        let snapshots = &mut self.get_mut().snapshots;
+        snapshots::update_account(snapshots, account, voting_balance(account));
+        snapshots::update_total_supply(snapshots, voting_total_supply());
+    }
+}
+
+/// The program's own balance holds the escrows of `transfer_and_call`,
+/// airdrops and vesting, which must cover what they owe.
+fn ensure_not_escrow(account: ActorId) {
+    if account == exec::program_id() {
+        panic!("Account is escrow")
+    };
+}
+
+/// Transfer made by the service itself, bypassing the checks of the base
+/// service but keeping snapshots and votes in sync. Returns whether balances
+/// changed along with the vote events to emit.
+fn internal_transfer(from: ActorId, to: ActorId, value: U256) -> utils::Result<(bool, Vec<Event>)> {
+    record_balances(from, to);
+
+    // Balances summing up to total supply, only `from` can fall short.
+    let mutated = vft_service::funcs::transfer(Storage::balances(), from, to, value)
+        .map_err(|_| utils::Error::InsufficientBalance)?;
+    let events = if mutated {
+        move_votes(from, to, value)
+    } else {
+        Vec::new()
+    };
+
+    Ok((mutated, events))
+}
+
+/// Pays `value` out of the escrow of airdrops and vesting to `to`.
+fn escrow_payout(to: ActorId, value: U256) -> (bool, Vec<Event>) {
+    // The program's own balance can't be spent but by its escrows, so it
+    // still covers what they owe.
+    internal_transfer(exec::program_id(), to, value).expect("Escrow covers the payout")
+}
+
+/// Returns `value` just escrowed from `funder` when the airdrop or vesting it
+/// was for couldn't be created.
+fn revert_escrow(funder: ActorId, value: U256) {
+    escrow_payout(funder, value);
+}
+
+/// Records balances of `from` and `to` into the current snapshot before a
+/// transfer between them.
+fn record_balances(from: ActorId, to: ActorId) {
+    let snapshots = &mut ExtendedService::new().get_mut().snapshots;
+    for account in [from, to] {
+        snapshots::update_account(snapshots, account, voting_balance(account));
+    }
+}
+
+/// Balance of `account`, on hold included, as snapshots and votes see it,
+/// that is without flash loans.
+fn voting_balance(account: ActorId) -> U256 {
+    let loans = &ExtendedService::new().get().flash_loans;
+    vft_service::funcs::total_balance_of(Storage::balances(), Storage::held(), account)
+        .saturating_sub(flash::loaned(loans, account))
+}
+
+/// Total supply as snapshots see it, that is without flash loans.
+fn voting_total_supply() -> U256 {
+    let loans = &ExtendedService::new().get().flash_loans;
+    Storage::total_supply().saturating_sub(loans.total)
+}
+
+/// Moves votes between delegates of `from` and `to` after their balances
+/// changed, zero address standing for mint and burn.
+fn move_votes(from: ActorId, to: ActorId, value: U256) -> Vec<Event> {
+    let votes = &mut ExtendedService::new().get_mut().votes;
+    let from = votes::delegates(votes, from);
+    let to = votes::delegates(votes, to);
+    let changes = services::utils::panicking(|| {
+        votes::move_voting_power(votes, from, to, value, exec::block_height())
+    });
+
+    votes_changed(changes)
+}
+
+fn votes_changed(changes: Vec<(ActorId, U256, U256)>) -> Vec<Event> {
+    changes
+        .into_iter()
+        .map(
+            |(delegate, previous_votes, new_votes)| Event::DelegateVotesChanged {
+                delegate,
+                previous_votes,
+                new_votes,
+            },
+        )
+        .collect()
+}
+
+/// Rejects the inherited methods while paused and transfers of frozen accounts,
+/// keeps snapshots and votes in sync with the transfers of the base service
+/// and charges their fee.
+struct ExtendedHooks;
+
+impl Hooks for ExtendedHooks {
+    fn before_call(&self) {
+        ExtendedService::new().ensure_not_paused();
+    }
+
+    fn before_transfer(&self, from: ActorId, to: ActorId, _value: U256) {
+        let service = ExtendedService::new();
+        service.ensure_not_frozen(from);
+        service.ensure_not_frozen(to);
+
+        record_balances(from, to);
+    }
+
+    fn before_escrow_release(&self, from: ActorId, to: ActorId, _value: U256) {
+        record_balances(from, to);
+    }
+
+    fn after_transfer(&self, route: &'static [u8], from: ActorId, to: ActorId, value: U256) {
+        let mut emitter = EventEmitter::<Event>::new(route);
+        for event in move_votes(from, to, value) {
+            emitter.emit_event(event).expect("Notification Error");
+        }
+    }
+
+    fn transfer_fee(&self, from: ActorId, to: ActorId, value: U256) -> Option<(ActorId, U256)> {
+        let fees = &ExtendedService::new().get().fees;
+        let fee = services::utils::panicking(|| fees::fee_of(fees, from, to, value));
+        if fee.is_zero() {
+            return None;
+        }
+
+        Some((fees.treasury, fee))
+    }
+
+    fn fee_charged(
+        &self,
+        route: &'static [u8],
+        from: ActorId,
+        to: ActorId,
+        treasury: ActorId,
+        fee: U256,
+    ) {
+        EventEmitter::<Event>::new(route)
+            .emit_event(Event::FeeCharged {
+                from,
+                to,
+                treasury,
+                fee,
+            })
+            .expect("Notification Error");
+    }
 }
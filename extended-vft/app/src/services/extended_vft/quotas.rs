@@ -0,0 +1,174 @@
+use super::utils::{Error, Result};
+use sails_rs::{collections::HashMap, prelude::*};
+
+/// Amount a minter may mint per epoch of `epoch_blocks` blocks. With zero
+/// `epoch_blocks` the amount is never refilled.
+#[derive(Clone)]
+pub struct MinterQuota {
+    pub amount: U256,
+    pub epoch_blocks: u32,
+    pub epoch_start: u32,
+    pub remaining: U256,
+}
+
+impl MinterQuota {
+    fn refill(&mut self, now: u32) {
+        if self.epoch_blocks == 0 {
+            return;
+        }
+
+        let elapsed = now.saturating_sub(self.epoch_start);
+        if elapsed >= self.epoch_blocks {
+            self.epoch_start += elapsed - elapsed % self.epoch_blocks;
+            self.remaining = self.amount;
+        }
+    }
+}
+
+/// Quotas of minters, those without one may mint without limit.
+pub type QuotasMap = HashMap<ActorId, MinterQuota>;
+
+pub fn set_minter_allowance(
+    quotas: &mut QuotasMap,
+    minter: ActorId,
+    amount: U256,
+    epoch_blocks: u32,
+    now: u32,
+) {
+    quotas.insert(
+        minter,
+        MinterQuota {
+            amount,
+            epoch_blocks,
+            epoch_start: now,
+            remaining: amount,
+        },
+    );
+}
+
+/// Removes the quota of `minter`, so that it may mint without limit again.
+/// Returns whether it had one.
+pub fn remove_minter_allowance(quotas: &mut QuotasMap, minter: ActorId) -> bool {
+    quotas.remove(&minter).is_some()
+}
+
+/// Returns what's left of the quota of `minter` in the current epoch,
+/// `None` if it may mint without limit.
+pub fn remaining_minter_allowance(quotas: &QuotasMap, minter: ActorId, now: u32) -> Option<U256> {
+    quotas.get(&minter).map(|quota| {
+        let mut quota = quota.clone();
+        quota.refill(now);
+        quota.remaining
+    })
+}
+
+/// Spends `value` of the quota of `minter`, returning what's left of it.
+pub fn spend_minter_allowance(
+    quotas: &mut QuotasMap,
+    minter: ActorId,
+    value: U256,
+    now: u32,
+) -> Result<Option<U256>> {
+    let Some(quota) = quotas.get_mut(&minter) else {
+        return Ok(None);
+    };
+
+    quota.refill(now);
+    quota.remaining = quota
+        .remaining
+        .checked_sub(value)
+        .ok_or(Error::MinterQuotaExceeded)?;
+
+    Ok(Some(quota.remaining))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alice() -> ActorId {
+        1u64.into()
+    }
+
+    fn bob() -> ActorId {
+        2u64.into()
+    }
+
+    #[test]
+    fn spend_minter_allowance() {
+        let mut quotas = QuotasMap::new();
+
+        // # Test case #1.
+        // Minter without quota isn't limited.
+        {
+            assert_eq!(
+                super::spend_minter_allowance(&mut quotas, alice(), U256::MAX, 1),
+                Ok(None)
+            );
+            assert_eq!(remaining_minter_allowance(&quotas, alice(), 1), None);
+        }
+
+        // # Test case #2.
+        // Quota is spent within an epoch.
+        {
+            set_minter_allowance(&mut quotas, alice(), 100.into(), 10, 5);
+            assert_eq!(
+                super::spend_minter_allowance(&mut quotas, alice(), 60.into(), 6),
+                Ok(Some(40.into()))
+            );
+            assert_eq!(
+                super::spend_minter_allowance(&mut quotas, alice(), 41.into(), 14),
+                Err(Error::MinterQuotaExceeded)
+            );
+            assert_eq!(
+                remaining_minter_allowance(&quotas, alice(), 14),
+                Some(40.into())
+            );
+        }
+
+        // # Test case #3.
+        // Quota is refilled once an epoch passes, epochs are aligned to the
+        // block quota was set at.
+        {
+            assert_eq!(
+                remaining_minter_allowance(&quotas, alice(), 15),
+                Some(100.into())
+            );
+            assert_eq!(
+                super::spend_minter_allowance(&mut quotas, alice(), 100.into(), 34),
+                Ok(Some(U256::zero()))
+            );
+            assert_eq!(quotas[&alice()].epoch_start, 25);
+            assert_eq!(
+                remaining_minter_allowance(&quotas, alice(), 35),
+                Some(100.into())
+            );
+        }
+
+        // # Test case #4.
+        // Quota without epochs is never refilled.
+        {
+            set_minter_allowance(&mut quotas, bob(), 100.into(), 0, 1);
+            assert_eq!(
+                super::spend_minter_allowance(&mut quotas, bob(), 100.into(), 2),
+                Ok(Some(U256::zero()))
+            );
+            assert_eq!(
+                remaining_minter_allowance(&quotas, bob(), u32::MAX),
+                Some(U256::zero())
+            );
+        }
+
+        // # Test case #5.
+        // Minter is no longer limited once its quota is removed.
+        {
+            assert!(remove_minter_allowance(&mut quotas, bob()));
+            assert!(!remove_minter_allowance(&mut quotas, bob()));
+            assert_eq!(remaining_minter_allowance(&quotas, bob(), 3), None);
+            assert_eq!(
+                super::spend_minter_allowance(&mut quotas, bob(), 100.into(), 3),
+                Ok(None)
+            );
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use super::utils::{Error, Result};
+use sails_rs::{collections::HashMap, prelude::*};
+
+/// Balances and total supply as of each snapshot. A value is recorded lazily,
+/// right before it changes for the first time after a snapshot was taken.
+#[derive(Default)]
+pub struct Snapshots {
+    /// Id of the latest snapshot, `0` if none was taken yet.
+    pub current_id: u64,
+    pub accounts: HashMap<ActorId, Vec<(u64, U256)>>,
+    pub total_supply: Vec<(u64, U256)>,
+}
+
+pub fn snapshot(snapshots: &mut Snapshots) -> Result<u64> {
+    snapshots.current_id = snapshots
+        .current_id
+        .checked_add(1)
+        .ok_or(Error::NumericOverflow)?;
+
+    Ok(snapshots.current_id)
+}
+
+/// Must be called before the balance of `account` changes, with its current value.
+pub fn update_account(snapshots: &mut Snapshots, account: ActorId, balance: U256) {
+    if snapshots.current_id == 0 {
+        return;
+    }
+
+    let values = snapshots.accounts.entry(account).or_default();
+    update(values, snapshots.current_id, balance);
+}
+
+/// Must be called before the total supply changes, with its current value.
+pub fn update_total_supply(snapshots: &mut Snapshots, total_supply: U256) {
+    if snapshots.current_id == 0 {
+        return;
+    }
+
+    update(
+        &mut snapshots.total_supply,
+        snapshots.current_id,
+        total_supply,
+    );
+}
+
+/// Returns the balance of `account` as of snapshot `id`, given its `current` one.
+pub fn balance_of_at(
+    snapshots: &Snapshots,
+    account: ActorId,
+    current: U256,
+    id: u64,
+) -> Result<U256> {
+    let values = snapshots
+        .accounts
+        .get(&account)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    value_at(snapshots.current_id, values, id, current)
+}
+
+pub fn total_supply_at(snapshots: &Snapshots, total_supply: U256, id: u64) -> Result<U256> {
+    value_at(
+        snapshots.current_id,
+        &snapshots.total_supply,
+        id,
+        total_supply,
+    )
+}
+
+fn update(values: &mut Vec<(u64, U256)>, current_id: u64, value: U256) {
+    if values.last().is_none_or(|(id, _)| *id < current_id) {
+        values.push((current_id, value));
+    }
+}
+
+// The first value recorded at or after `id` is the one the snapshot saw;
+// if nothing changed since the snapshot, the current value is returned.
+fn value_at(current_id: u64, values: &[(u64, U256)], id: u64, current: U256) -> Result<U256> {
+    if id == 0 || id > current_id {
+        return Err(Error::NonexistentSnapshot);
+    }
+
+    let index = values.partition_point(|(recorded, _)| *recorded < id);
+
+    Ok(values.get(index).map_or(current, |(_, value)| *value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alice() -> ActorId {
+        1u64.into()
+    }
+
+    fn bob() -> ActorId {
+        2u64.into()
+    }
+
+    #[test]
+    fn balance_of_at() {
+        let mut snapshots = Snapshots::default();
+        let mut balance = U256::from(100);
+
+        // # Test case #1.
+        // Nothing is recorded and no snapshot exists before the first one is taken.
+        {
+            update_account(&mut snapshots, alice(), 100.into());
+            assert!(snapshots.accounts.is_empty());
+            assert_eq!(
+                super::balance_of_at(&snapshots, alice(), balance, 1),
+                Err(Error::NonexistentSnapshot)
+            );
+        }
+
+        // # Test case #2.
+        // Unchanged balance is read from the current one.
+        {
+            assert_eq!(snapshot(&mut snapshots), Ok(1));
+            assert_eq!(
+                super::balance_of_at(&snapshots, alice(), balance, 1),
+                Ok(100.into())
+            );
+        }
+
+        // # Test case #3.
+        // Only the first change after a snapshot is recorded.
+        {
+            update_account(&mut snapshots, alice(), 100.into());
+            update_account(&mut snapshots, alice(), 60.into());
+            balance = 30.into();
+
+            assert_eq!(snapshots.accounts[&alice()], vec![(1, 100.into())]);
+            assert_eq!(
+                super::balance_of_at(&snapshots, alice(), balance, 1),
+                Ok(100.into())
+            );
+        }
+
+        // # Test case #4.
+        // Snapshots with no changes in between share the recorded value.
+        {
+            assert_eq!(snapshot(&mut snapshots), Ok(2));
+            assert_eq!(snapshot(&mut snapshots), Ok(3));
+            update_account(&mut snapshots, alice(), 30.into());
+            balance = U256::zero();
+
+            assert_eq!(
+                super::balance_of_at(&snapshots, alice(), balance, 1),
+                Ok(100.into())
+            );
+            assert_eq!(
+                super::balance_of_at(&snapshots, alice(), balance, 2),
+                Ok(30.into())
+            );
+            assert_eq!(
+                super::balance_of_at(&snapshots, alice(), balance, 3),
+                Ok(30.into())
+            );
+            assert_eq!(
+                super::balance_of_at(&snapshots, bob(), U256::zero(), 3),
+                Ok(U256::zero())
+            );
+        }
+
+        // # Test case #5.
+        // Future snapshot doesn't exist.
+        {
+            assert_eq!(
+                super::balance_of_at(&snapshots, alice(), balance, 4),
+                Err(Error::NonexistentSnapshot)
+            );
+        }
+    }
+
+    #[test]
+    fn total_supply_at() {
+        let mut snapshots = Snapshots::default();
+
+        assert_eq!(snapshot(&mut snapshots), Ok(1));
+        update_total_supply(&mut snapshots, 100.into());
+        assert_eq!(snapshot(&mut snapshots), Ok(2));
+
+        assert_eq!(
+            super::total_supply_at(&snapshots, 150.into(), 1),
+            Ok(100.into())
+        );
+        assert_eq!(
+            super::total_supply_at(&snapshots, 150.into(), 2),
+            Ok(150.into())
+        );
+        assert_eq!(
+            super::total_supply_at(&snapshots, 150.into(), 0),
+            Err(Error::NonexistentSnapshot)
+        );
+    }
+}
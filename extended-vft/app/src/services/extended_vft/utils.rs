@@ -0,0 +1,28 @@
+use sails_rs::prelude::*;
+
+pub type Result<T, E = Error> = core::result::Result<T, E>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Encode, Decode, TypeInfo)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub enum Error {
+    InsufficientAllowance,
+    InsufficientBalance,
+    NumericOverflow,
+    Underflow,
+    NonexistentSnapshot,
+    FutureLookup,
+    MaxSupplyExceeded,
+    InvalidMaxSupply,
+    MinterQuotaExceeded,
+    NonexistentCampaign,
+    CampaignExpired,
+    CampaignNotExpired,
+    AlreadyClaimed,
+    InvalidProof,
+    NonexistentSchedule,
+    InvalidSchedule,
+    NotRevocable,
+    InvalidFee,
+    FlashLoanExceeded,
+}
@@ -0,0 +1,281 @@
+use super::utils::{Error, Result};
+use sails_rs::{collections::HashMap, prelude::*};
+
+/// Tokens escrowed by `funder` that vest to `beneficiary` linearly over
+/// `duration` ms from `start`, nothing being vested before `cliff` ms pass.
+pub struct Schedule {
+    pub funder: ActorId,
+    pub beneficiary: ActorId,
+    pub total: U256,
+    pub released: U256,
+    pub start: u64,
+    pub cliff: u64,
+    pub duration: u64,
+    pub revocable: bool,
+    /// Once revoked, `total` is what had vested by then.
+    pub revoked: bool,
+}
+
+impl Schedule {
+    fn vested(&self, now: u64) -> U256 {
+        let elapsed = now.saturating_sub(self.start);
+
+        if self.revoked {
+            self.total
+        } else if now < self.start {
+            U256::zero()
+        } else if elapsed >= self.duration {
+            self.total
+        } else if elapsed < self.cliff {
+            U256::zero()
+        } else {
+            // Can't overflow: `elapsed < duration`, so the result is below `total`.
+            (self.total.full_mul(elapsed.into()) / self.duration)
+                .try_into()
+                .expect("Infallible")
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Vesting {
+    pub last_id: u64,
+    pub schedules: HashMap<u64, Schedule>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_schedule(
+    vesting: &mut Vesting,
+    funder: ActorId,
+    beneficiary: ActorId,
+    total: U256,
+    start: u64,
+    cliff: u64,
+    duration: u64,
+    revocable: bool,
+) -> Result<u64> {
+    if beneficiary.is_zero() || cliff > duration {
+        return Err(Error::InvalidSchedule);
+    }
+
+    let id = vesting
+        .last_id
+        .checked_add(1)
+        .ok_or(Error::NumericOverflow)?;
+
+    vesting.last_id = id;
+    vesting.schedules.insert(
+        id,
+        Schedule {
+            funder,
+            beneficiary,
+            total,
+            released: U256::zero(),
+            start,
+            cliff,
+            duration,
+            revocable,
+            revoked: false,
+        },
+    );
+
+    Ok(id)
+}
+
+pub fn vested_amount(vesting: &Vesting, schedule: u64, now: u64) -> Result<U256> {
+    let schedule = vesting
+        .schedules
+        .get(&schedule)
+        .ok_or(Error::NonexistentSchedule)?;
+
+    Ok(schedule.vested(now))
+}
+
+pub fn releasable_amount(vesting: &Vesting, schedule: u64, now: u64) -> Result<U256> {
+    let schedule = vesting
+        .schedules
+        .get(&schedule)
+        .ok_or(Error::NonexistentSchedule)?;
+
+    Ok(schedule.vested(now) - schedule.released)
+}
+
+/// Marks everything vested so far as released, returning the beneficiary
+/// and the amount to transfer to it.
+pub fn release(vesting: &mut Vesting, schedule: u64, now: u64) -> Result<(ActorId, U256)> {
+    let schedule = vesting
+        .schedules
+        .get_mut(&schedule)
+        .ok_or(Error::NonexistentSchedule)?;
+
+    let amount = schedule.vested(now) - schedule.released;
+    schedule.released = schedule.released.saturating_add(amount);
+
+    Ok((schedule.beneficiary, amount))
+}
+
+/// Stops vesting of `schedule`, returning its funder and the unvested amount
+/// to transfer back. What had vested stays releasable.
+pub fn revoke(vesting: &mut Vesting, schedule: u64, now: u64) -> Result<(ActorId, U256)> {
+    let schedule = vesting
+        .schedules
+        .get_mut(&schedule)
+        .ok_or(Error::NonexistentSchedule)?;
+
+    if !schedule.revocable || schedule.revoked {
+        return Err(Error::NotRevocable);
+    }
+
+    let vested = schedule.vested(now);
+    let unvested = schedule.total - vested;
+    schedule.total = vested;
+    schedule.revoked = true;
+
+    Ok((schedule.funder, unvested))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alice() -> ActorId {
+        1u64.into()
+    }
+
+    fn bob() -> ActorId {
+        2u64.into()
+    }
+
+    #[test]
+    fn release() {
+        let mut vesting = Vesting::default();
+
+        // # Test case #1.
+        // Cliff can't be longer than vesting, beneficiary can't be zero.
+        {
+            assert_eq!(
+                create_schedule(
+                    &mut vesting,
+                    alice(),
+                    bob(),
+                    1000.into(),
+                    100,
+                    11,
+                    10,
+                    false
+                ),
+                Err(Error::InvalidSchedule)
+            );
+            assert_eq!(
+                create_schedule(
+                    &mut vesting,
+                    alice(),
+                    ActorId::zero(),
+                    1000.into(),
+                    100,
+                    0,
+                    10,
+                    false
+                ),
+                Err(Error::InvalidSchedule)
+            );
+            assert_eq!(
+                create_schedule(
+                    &mut vesting,
+                    alice(),
+                    bob(),
+                    1000.into(),
+                    100,
+                    20,
+                    100,
+                    false
+                ),
+                Ok(1)
+            );
+        }
+
+        // # Test case #2.
+        // Nothing vests before the cliff, then vesting is linear from start.
+        {
+            assert_eq!(vested_amount(&vesting, 1, 50), Ok(U256::zero()));
+            assert_eq!(vested_amount(&vesting, 1, 119), Ok(U256::zero()));
+            assert_eq!(vested_amount(&vesting, 1, 120), Ok(200.into()));
+            assert_eq!(vested_amount(&vesting, 1, 200), Ok(1000.into()));
+            assert_eq!(vested_amount(&vesting, 1, u64::MAX), Ok(1000.into()));
+            assert_eq!(
+                vested_amount(&vesting, 2, 200),
+                Err(Error::NonexistentSchedule)
+            );
+        }
+
+        // # Test case #3.
+        // Only what wasn't released yet is releasable.
+        {
+            assert_eq!(
+                super::release(&mut vesting, 1, 150),
+                Ok((bob(), 500.into()))
+            );
+            assert_eq!(releasable_amount(&vesting, 1, 150), Ok(U256::zero()));
+            assert_eq!(releasable_amount(&vesting, 1, 175), Ok(250.into()));
+            assert_eq!(
+                super::release(&mut vesting, 1, 300),
+                Ok((bob(), 500.into()))
+            );
+            assert_eq!(
+                super::release(&mut vesting, 1, 300),
+                Ok((bob(), U256::zero()))
+            );
+        }
+
+        // # Test case #4.
+        // Schedule isn't revocable unless created so.
+        {
+            assert_eq!(
+                super::revoke(&mut vesting, 1, 300),
+                Err(Error::NotRevocable)
+            );
+        }
+    }
+
+    #[test]
+    fn zero_duration() {
+        let mut vesting = Vesting::default();
+        assert_eq!(
+            create_schedule(&mut vesting, alice(), bob(), 1000.into(), 100, 0, 0, false),
+            Ok(1)
+        );
+
+        // Everything vests at start, nothing before.
+        assert_eq!(vested_amount(&vesting, 1, 99), Ok(U256::zero()));
+        assert_eq!(
+            super::release(&mut vesting, 1, 99),
+            Ok((bob(), U256::zero()))
+        );
+        assert_eq!(vested_amount(&vesting, 1, 100), Ok(1000.into()));
+    }
+
+    #[test]
+    fn revoke() {
+        let mut vesting = Vesting::default();
+        assert_eq!(
+            create_schedule(&mut vesting, alice(), bob(), 1000.into(), 100, 0, 100, true),
+            Ok(1)
+        );
+        assert_eq!(
+            super::release(&mut vesting, 1, 120),
+            Ok((bob(), 200.into()))
+        );
+
+        // Unvested tokens go back to funder, vested ones stay releasable.
+        assert_eq!(
+            super::revoke(&mut vesting, 1, 150),
+            Ok((alice(), 500.into()))
+        );
+        assert_eq!(vested_amount(&vesting, 1, 200), Ok(500.into()));
+        assert_eq!(releasable_amount(&vesting, 1, 200), Ok(300.into()));
+        assert_eq!(
+            super::revoke(&mut vesting, 1, 200),
+            Err(Error::NotRevocable)
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use super::utils::{Error, Result};
+use sails_rs::{collections::HashMap, prelude::*};
+
+/// Voting power delegated to each account, checkpointed by block height.
+#[derive(Default)]
+pub struct Votes {
+    pub delegates: HashMap<ActorId, ActorId>,
+    pub checkpoints: HashMap<ActorId, Vec<(u32, U256)>>,
+}
+
+/// Returns the delegate of `account`, zero address if it has not delegated.
+pub fn delegates(votes: &Votes, account: ActorId) -> ActorId {
+    votes.delegates.get(&account).copied().unwrap_or_default()
+}
+
+/// Sets `to` as the delegate of `delegator`, returning the previous one.
+/// Delegating to zero address removes the delegation.
+pub fn delegate(votes: &mut Votes, delegator: ActorId, to: ActorId) -> ActorId {
+    let previous = if to.is_zero() {
+        votes.delegates.remove(&delegator)
+    } else {
+        votes.delegates.insert(delegator, to)
+    };
+
+    previous.unwrap_or_default()
+}
+
+pub fn get_votes(votes: &Votes, account: ActorId) -> U256 {
+    votes
+        .checkpoints
+        .get(&account)
+        .and_then(|checkpoints| checkpoints.last())
+        .map(|(_, votes)| *votes)
+        .unwrap_or_default()
+}
+
+/// Returns the votes of `account` at the end of `block`, which must be
+/// already finished.
+pub fn get_past_votes(
+    votes: &Votes,
+    account: ActorId,
+    block: u32,
+    current_block: u32,
+) -> Result<U256> {
+    if block >= current_block {
+        return Err(Error::FutureLookup);
+    }
+
+    let Some(checkpoints) = votes.checkpoints.get(&account) else {
+        return Ok(U256::zero());
+    };
+
+    let index = checkpoints.partition_point(|(recorded, _)| *recorded <= block);
+
+    Ok(index
+        .checked_sub(1)
+        .map(|index| checkpoints[index].1)
+        .unwrap_or_default())
+}
+
+/// Moves `value` votes from delegate `from` to delegate `to`, zero address
+/// standing for none. Returns `(delegate, previous_votes, new_votes)` for
+/// each delegate whose votes changed.
+pub fn move_voting_power(
+    votes: &mut Votes,
+    from: ActorId,
+    to: ActorId,
+    value: U256,
+    block: u32,
+) -> Result<Vec<(ActorId, U256, U256)>> {
+    let mut changes = Vec::new();
+
+    if from == to || value.is_zero() {
+        return Ok(changes);
+    }
+
+    if !from.is_zero() {
+        let previous = get_votes(votes, from);
+        let new = previous.checked_sub(value).ok_or(Error::Underflow)?;
+        changes.push((from, previous, new));
+    }
+
+    if !to.is_zero() {
+        let previous = get_votes(votes, to);
+        let new = previous.checked_add(value).ok_or(Error::NumericOverflow)?;
+        changes.push((to, previous, new));
+    }
+
+    for (delegate, _, new) in &changes {
+        let checkpoints = votes.checkpoints.entry(*delegate).or_default();
+        match checkpoints.last_mut() {
+            Some((recorded, votes)) if *recorded == block => *votes = *new,
+            _ => checkpoints.push((block, *new)),
+        }
+    }
+
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alice() -> ActorId {
+        1u64.into()
+    }
+
+    fn bob() -> ActorId {
+        2u64.into()
+    }
+
+    #[test]
+    fn delegate() {
+        let mut votes = Votes::default();
+
+        assert_eq!(delegates(&votes, alice()), ActorId::zero());
+        assert_eq!(super::delegate(&mut votes, alice(), bob()), ActorId::zero());
+        assert_eq!(delegates(&votes, alice()), bob());
+        assert_eq!(super::delegate(&mut votes, alice(), alice()), bob());
+        assert_eq!(
+            super::delegate(&mut votes, alice(), ActorId::zero()),
+            alice()
+        );
+        assert!(votes.delegates.is_empty());
+    }
+
+    #[test]
+    fn move_voting_power() {
+        let mut votes = Votes::default();
+
+        // # Test case #1.
+        // Nothing changes for the same delegate, zero value or zero addresses.
+        {
+            assert_eq!(
+                super::move_voting_power(&mut votes, alice(), alice(), 100.into(), 1),
+                Ok(vec![])
+            );
+            assert_eq!(
+                super::move_voting_power(&mut votes, alice(), bob(), U256::zero(), 1),
+                Ok(vec![])
+            );
+            assert_eq!(
+                super::move_voting_power(
+                    &mut votes,
+                    ActorId::zero(),
+                    ActorId::zero(),
+                    100.into(),
+                    1
+                ),
+                Ok(vec![])
+            );
+            assert!(votes.checkpoints.is_empty());
+        }
+
+        // # Test case #2.
+        // Minting to a delegate adds votes, moving them within a block
+        // overwrites the checkpoint.
+        {
+            assert_eq!(
+                super::move_voting_power(&mut votes, ActorId::zero(), alice(), 100.into(), 1),
+                Ok(vec![(alice(), U256::zero(), 100.into())])
+            );
+            assert_eq!(
+                super::move_voting_power(&mut votes, alice(), bob(), 40.into(), 1),
+                Ok(vec![
+                    (alice(), 100.into(), 60.into()),
+                    (bob(), U256::zero(), 40.into())
+                ])
+            );
+            assert_eq!(votes.checkpoints[&alice()], vec![(1, 60.into())]);
+            assert_eq!(get_votes(&votes, bob()), 40.into());
+        }
+
+        // # Test case #3.
+        // Error on moving more votes than delegate has.
+        {
+            assert_eq!(
+                super::move_voting_power(&mut votes, bob(), alice(), 41.into(), 2),
+                Err(Error::Underflow)
+            );
+        }
+
+        // # Test case #4.
+        // Past votes are looked up by block.
+        {
+            assert_eq!(
+                super::move_voting_power(&mut votes, alice(), ActorId::zero(), 60.into(), 3),
+                Ok(vec![(alice(), 60.into(), U256::zero())])
+            );
+
+            assert_eq!(get_past_votes(&votes, alice(), 0, 4), Ok(U256::zero()));
+            assert_eq!(get_past_votes(&votes, alice(), 1, 4), Ok(60.into()));
+            assert_eq!(get_past_votes(&votes, alice(), 2, 4), Ok(60.into()));
+            assert_eq!(get_past_votes(&votes, alice(), 3, 4), Ok(U256::zero()));
+            assert_eq!(get_past_votes(&votes, bob(), 3, 4), Ok(40.into()));
+            assert_eq!(
+                get_past_votes(&votes, alice(), 4, 4),
+                Err(Error::FutureLookup)
+            );
+        }
+    }
+}
@@ -1,13 +1,64 @@
 use extended_vft_client::{
     traits::{ExtendedVftFactory, Vft},
-    ExtendedVftFactory as Factory, Vft as VftClient,
+    ExtendedVftFactory as Factory, ExtendedVftUtilsError as ExtendedError, Vft as VftClient,
+    VftServiceUtilsError as Error,
 };
+use gtest::{Log, Program, WasmProgram};
 use sails_rs::calls::*;
 use sails_rs::gtest::{calls::*, System};
+use sails_rs::{ActorId, Encode, U256};
 
 pub const ADMIN_ID: u64 = 10;
 pub const USER_ID: [u64; 2] = [11, 12];
 
+/// A program replying with the same payload to every message.
+#[derive(Clone, Debug)]
+struct MockReceiver(Vec<u8>);
+
+impl WasmProgram for MockReceiver {
+    fn init(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        Ok(None)
+    }
+
+    fn handle(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        Ok(Some(self.0.clone()))
+    }
+
+    fn clone_boxed(&self) -> Box<dyn WasmProgram> {
+        Box::new(self.clone())
+    }
+
+    fn state(&mut self) -> Result<Vec<u8>, &'static str> {
+        Ok(vec![])
+    }
+}
+
+/// Deploys a program replying `reply` to every message.
+fn mock_receiver(system: &System, reply: impl Encode) -> ActorId {
+    let receiver = Program::mock(system, MockReceiver(reply.encode()));
+    receiver.send_bytes(ADMIN_ID, []);
+    system.run_next_block();
+    receiver.id()
+}
+
+/// Replies `reply` to the message `account` got from `program_id`, running
+/// the block that handles it. Lets the test act while the program waits for
+/// the reply of `account`, as a receiver program could.
+fn reply_from_mailbox(
+    program_space: &GTestRemoting,
+    program_id: ActorId,
+    account: u64,
+    reply: impl Encode,
+) {
+    let log = Log::builder().source(program_id).dest(account);
+    program_space
+        .system()
+        .get_mailbox(account)
+        .reply(log, reply, 0)
+        .unwrap();
+    program_space.run_next_block();
+}
+
 #[tokio::test]
 async fn test_basic_function() {
     let system = System::new();
@@ -34,6 +85,7 @@ async fn test_basic_function() {
         .mint(ADMIN_ID.into(), 1_000.into())
         .send_recv(extended_vft_id)
         .await
+        .unwrap()
         .unwrap();
     // check balance
     let balance = client
@@ -48,6 +100,7 @@ async fn test_basic_function() {
         .burn(ADMIN_ID.into(), 100.into())
         .send_recv(extended_vft_id)
         .await
+        .unwrap()
         .unwrap();
     // check balance
     let balance = client
@@ -62,6 +115,7 @@ async fn test_basic_function() {
         .transfer(USER_ID[0].into(), 100.into())
         .send_recv(extended_vft_id)
         .await
+        .unwrap()
         .unwrap();
     // check balance
     let balance = client
@@ -101,6 +155,7 @@ async fn test_basic_function() {
         .transfer_from(ADMIN_ID.into(), USER_ID[0].into(), 100.into())
         .send_recv(extended_vft_id)
         .await
+        .unwrap()
         .unwrap();
     // check balance
     let balance = client
@@ -166,6 +221,7 @@ async fn test_grant_role() {
         .with_args(|args| args.with_actor_id(USER_ID[0].into()))
         .send_recv(extended_vft_id)
         .await
+        .unwrap()
         .unwrap();
     assert!(res);
     let balance = client
@@ -196,6 +252,7 @@ async fn test_grant_role() {
         .with_args(|args| args.with_actor_id(USER_ID[0].into()))
         .send_recv(extended_vft_id)
         .await
+        .unwrap()
         .unwrap();
     assert!(res);
     let balance = client
@@ -237,3 +294,1873 @@ async fn test_grant_role() {
     let burners = client.burners().recv(extended_vft_id).await.unwrap();
     assert_eq!(burners, vec![ADMIN_ID.into()]);
 }
+
+#[tokio::test]
+async fn test_transfer_and_call_rollback() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+    // a program without `VftReceiver` service to receive tokens
+    let receiver_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "456")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space);
+    // mint
+    client
+        .mint(ADMIN_ID.into(), 1_000.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // transfer and call is rejected by receiver
+    let res = client
+        .transfer_and_call(receiver_id, 100.into(), vec![])
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!res);
+    // check balances are restored
+    let balance = client
+        .balance_of(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 1_000.into());
+    let balance = client
+        .balance_of(receiver_id)
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 0.into());
+}
+
+#[tokio::test]
+async fn test_transfer_and_call() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+    let receiver_id = mock_receiver(&system, ("VftReceiver", "OnVftReceived", true));
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space);
+    // mint
+    client
+        .mint(ADMIN_ID.into(), 1_000.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // transfer and call is accepted by receiver
+    let res = client
+        .transfer_and_call(receiver_id, 100.into(), vec![])
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(res);
+    let balance = client
+        .balance_of(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 900.into());
+    let balance = client
+        .balance_of(receiver_id)
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 100.into());
+    // nothing is left in escrow
+    let balance = client
+        .balance_of(extended_vft_id)
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 0.into());
+}
+
+#[tokio::test]
+async fn test_delegate_during_transfer_and_call() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+    system.mint_to(USER_ID[0], 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space.clone());
+    // mint and delegate to self
+    client
+        .mint(ADMIN_ID.into(), 1_000.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    client
+        .delegate(ADMIN_ID.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+
+    // delegate while the receiver is yet to reply
+    let program_space = program_space.with_block_run_mode(BlockRunMode::Manual);
+    let mut client = VftClient::new(program_space.clone());
+    let mut delegator = VftClient::new(program_space.clone());
+    let transfer = client
+        .transfer_and_call(USER_ID[0].into(), 100.into(), vec![])
+        .send(extended_vft_id)
+        .await
+        .unwrap();
+    let delegate = delegator
+        .delegate(USER_ID[1].into())
+        .send(extended_vft_id)
+        .await
+        .unwrap();
+    program_space.run_next_block();
+    delegate.recv().await.unwrap();
+    reply_from_mailbox(
+        &program_space,
+        extended_vft_id,
+        USER_ID[0],
+        ("VftReceiver", "OnVftReceived", true),
+    );
+    assert!(transfer.recv().await.unwrap().unwrap());
+
+    // votes follow the balance left to the sender
+    let balance = client
+        .balance_of(USER_ID[0].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 100.into());
+    let votes = client
+        .get_votes(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(votes, 0.into());
+    let votes = client
+        .get_votes(USER_ID[1].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(votes, 900.into());
+}
+
+#[tokio::test]
+async fn test_snapshots() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space);
+    // mint
+    client
+        .mint(ADMIN_ID.into(), 1_000.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // snapshot
+    let id = client
+        .snapshot()
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(id, 1);
+
+    // transfer and mint after snapshot
+    client
+        .transfer(USER_ID[0].into(), 100.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    client
+        .mint(USER_ID[0].into(), 50.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // check balances at snapshot
+    let balance = client
+        .balance_of_at(ADMIN_ID.into(), id)
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 1_000.into());
+    let balance = client
+        .balance_of_at(USER_ID[0].into(), id)
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 0.into());
+    let total_supply = client
+        .total_supply_at(id)
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(total_supply, 1_000.into());
+
+    // nonexistent snapshot
+    let res = client
+        .balance_of_at(ADMIN_ID.into(), 2)
+        .recv(extended_vft_id)
+        .await;
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+async fn test_votes() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+    system.mint_to(USER_ID[0], 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space.clone());
+    // mint
+    client
+        .mint(ADMIN_ID.into(), 1_000.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // delegate to self
+    client
+        .delegate(ADMIN_ID.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    let delegate = client
+        .delegates(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(delegate, ADMIN_ID.into());
+    let votes = client
+        .get_votes(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(votes, 1_000.into());
+    let delegated_at = program_space.system().block_height();
+
+    // transfer moves votes
+    client
+        .transfer(USER_ID[0].into(), 100.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let votes = client
+        .get_votes(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(votes, 900.into());
+
+    // past votes
+    let votes = client
+        .get_past_votes(ADMIN_ID.into(), delegated_at)
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(votes, 1_000.into());
+    let res = client
+        .get_past_votes(ADMIN_ID.into(), program_space.system().block_height() + 1)
+        .recv(extended_vft_id)
+        .await;
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+async fn test_pause() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space);
+    // mint
+    client
+        .mint(ADMIN_ID.into(), 1_000.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    client
+        .approve(USER_ID[0].into(), 100.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    client
+        .approve(USER_ID[1].into(), 100.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+
+    // pause
+    let res = client.pause().send_recv(extended_vft_id).await.unwrap();
+    assert!(res);
+    let paused = client.paused().recv(extended_vft_id).await.unwrap();
+    assert!(paused);
+
+    // mint, transfer and approve are rejected
+    let res = client
+        .mint(ADMIN_ID.into(), 1_000.into())
+        .send_recv(extended_vft_id)
+        .await;
+    assert!(res.is_err());
+    let res = client
+        .transfer(USER_ID[0].into(), 100.into())
+        .send_recv(extended_vft_id)
+        .await;
+    assert!(res.is_err());
+    let res = client
+        .approve(USER_ID[0].into(), 200.into())
+        .send_recv(extended_vft_id)
+        .await;
+    assert!(res.is_err());
+
+    // allowance can still be lowered
+    let res = client
+        .decrease_allowance(USER_ID[0].into(), 50.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(res);
+    let res = client
+        .approve(USER_ID[0].into(), 0.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert!(res);
+    let res = client
+        .revoke_all_allowances()
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert!(res);
+
+    // unpause
+    let res = client.unpause().send_recv(extended_vft_id).await.unwrap();
+    assert!(res);
+    let res = client
+        .transfer(USER_ID[0].into(), 100.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(res);
+}
+
+#[tokio::test]
+async fn test_panicking_errors() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new_with_panicking_errors("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space);
+    client
+        .mint(ADMIN_ID.into(), 100.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // errors end in an error reply instead of being returned
+    let res = client
+        .transfer(USER_ID[0].into(), 200.into())
+        .send_recv(extended_vft_id)
+        .await;
+    assert!(res.is_err());
+    let res = client
+        .burn(ADMIN_ID.into(), 200.into())
+        .send_recv(extended_vft_id)
+        .await;
+    assert!(res.is_err());
+    let res = client
+        .transfer(USER_ID[0].into(), 50.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Ok(true));
+}
+
+#[tokio::test]
+async fn test_max_supply() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new_with_max_supply("name".to_string(), "symbol".to_string(), 10, 1_000.into())
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space);
+    let max_supply = client.max_supply().recv(extended_vft_id).await.unwrap();
+    assert_eq!(max_supply, Some(1_000.into()));
+
+    // mint up to max supply
+    client
+        .mint(ADMIN_ID.into(), 900.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let res = client
+        .mint(ADMIN_ID.into(), 101.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Err(ExtendedError::MaxSupplyExceeded));
+
+    // max supply can only be lowered
+    let res = client
+        .set_max_supply(2_000.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Err(ExtendedError::InvalidMaxSupply));
+    let res = client
+        .set_max_supply(900.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(res);
+    let res = client
+        .mint(ADMIN_ID.into(), 1.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Err(ExtendedError::MaxSupplyExceeded));
+}
+
+#[tokio::test]
+async fn test_minter_allowance() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space);
+    // minter isn't limited by default
+    let remaining = client
+        .remaining_minter_allowance(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(remaining, None);
+
+    // set allowance
+    client
+        .set_minter_allowance(ADMIN_ID.into(), 100.into(), 1_000)
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+
+    // mint within allowance
+    client
+        .mint(ADMIN_ID.into(), 60.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let remaining = client
+        .remaining_minter_allowance(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(remaining, Some(40.into()));
+
+    // mint above allowance
+    let res = client
+        .mint(ADMIN_ID.into(), 41.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Err(ExtendedError::MinterQuotaExceeded));
+    let total_supply = client.total_supply().recv(extended_vft_id).await.unwrap();
+    assert_eq!(total_supply, 60.into());
+
+    // remove allowance
+    let res = client
+        .remove_minter_allowance(ADMIN_ID.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert!(res);
+    let remaining = client
+        .remaining_minter_allowance(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(remaining, None);
+    client
+        .mint(ADMIN_ID.into(), 41.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_freeze() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+    system.mint_to(USER_ID[0], 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space);
+    // mint
+    client
+        .mint(USER_ID[0].into(), 1_000.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // freeze
+    let res = client
+        .freeze(USER_ID[0].into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert!(res);
+    let frozen = client
+        .is_frozen(USER_ID[0].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert!(frozen);
+
+    // frozen account can't send
+    let res = client
+        .transfer(ADMIN_ID.into(), 100.into())
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await;
+    assert!(res.is_err());
+
+    // forced transfer
+    let res = client
+        .force_transfer(USER_ID[0].into(), ADMIN_ID.into(), 100.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(res);
+    let balance = client
+        .balance_of(USER_ID[0].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 900.into());
+
+    // frozen account can't receive
+    let res = client
+        .transfer(USER_ID[0].into(), 100.into())
+        .send_recv(extended_vft_id)
+        .await;
+    assert!(res.is_err());
+
+    // only compliance can freeze
+    let res = client
+        .unfreeze(USER_ID[0].into())
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await;
+    assert!(res.is_err());
+
+    // unfreeze
+    let res = client
+        .unfreeze(USER_ID[0].into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert!(res);
+    let res = client
+        .transfer(USER_ID[0].into(), 100.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(res);
+}
+
+#[tokio::test]
+async fn test_freeze_during_transfer_and_call() {
+    const TREASURY_ID: u64 = 13;
+
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+    system.mint_to(USER_ID[0], 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space.clone());
+    // mint and set fee
+    client
+        .mint(ADMIN_ID.into(), 1_000.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    client
+        .set_transfer_fee(100, TREASURY_ID.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // the escrow of the program can't be frozen, burned or forced out
+    let res = client
+        .freeze(extended_vft_id)
+        .send_recv(extended_vft_id)
+        .await;
+    assert!(res.is_err());
+    let res = client
+        .burn(extended_vft_id, 1.into())
+        .send_recv(extended_vft_id)
+        .await;
+    assert!(res.is_err());
+    let res = client
+        .force_transfer(extended_vft_id, ADMIN_ID.into(), 1.into())
+        .send_recv(extended_vft_id)
+        .await;
+    assert!(res.is_err());
+
+    // the escrow is settled even if every party got frozen meanwhile
+    let program_space = program_space.with_block_run_mode(BlockRunMode::Manual);
+    let mut client = VftClient::new(program_space.clone());
+    let mut compliance = VftClient::new(program_space.clone());
+    let transfer = client
+        .transfer_and_call(USER_ID[0].into(), 100.into(), vec![])
+        .send(extended_vft_id)
+        .await
+        .unwrap();
+    program_space.run_next_block();
+    for account in [ADMIN_ID, USER_ID[0], TREASURY_ID] {
+        let freeze = compliance
+            .freeze(account.into())
+            .send(extended_vft_id)
+            .await
+            .unwrap();
+        program_space.run_next_block();
+        assert!(freeze.recv().await.unwrap());
+    }
+    reply_from_mailbox(
+        &program_space,
+        extended_vft_id,
+        USER_ID[0],
+        ("VftReceiver", "OnVftReceived", true),
+    );
+    assert_eq!(transfer.recv().await.unwrap(), Ok(true));
+    for (account, expected) in [(ADMIN_ID, 900), (USER_ID[0], 99), (TREASURY_ID, 1)] {
+        let balance = client
+            .balance_of(account.into())
+            .recv(extended_vft_id)
+            .await
+            .unwrap();
+        assert_eq!(balance, expected.into());
+    }
+
+    // and returned to a sender frozen meanwhile
+    for account in [ADMIN_ID, USER_ID[0]] {
+        let unfreeze = compliance
+            .unfreeze(account.into())
+            .send(extended_vft_id)
+            .await
+            .unwrap();
+        program_space.run_next_block();
+        assert!(unfreeze.recv().await.unwrap());
+    }
+    let transfer = client
+        .transfer_and_call(USER_ID[0].into(), 100.into(), vec![])
+        .send(extended_vft_id)
+        .await
+        .unwrap();
+    program_space.run_next_block();
+    let freeze = compliance
+        .freeze(ADMIN_ID.into())
+        .send(extended_vft_id)
+        .await
+        .unwrap();
+    program_space.run_next_block();
+    assert!(freeze.recv().await.unwrap());
+    reply_from_mailbox(
+        &program_space,
+        extended_vft_id,
+        USER_ID[0],
+        ("VftReceiver", "OnVftReceived", false),
+    );
+    assert_eq!(transfer.recv().await.unwrap(), Ok(false));
+    let balance = client
+        .balance_of(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 900.into());
+}
+
+#[tokio::test]
+async fn test_burn_self_and_burn_from() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+    system.mint_to(USER_ID[0], 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space);
+    // mint
+    client
+        .mint(USER_ID[0].into(), 1_000.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // holder burns own tokens
+    let res = client
+        .burn_self(100.into())
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(res);
+
+    // burn without allowance
+    let res = client
+        .burn_from(USER_ID[0].into(), 100.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Err(ExtendedError::InsufficientAllowance));
+
+    // burn within allowance
+    client
+        .approve(ADMIN_ID.into(), 100.into())
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    let res = client
+        .burn_from(USER_ID[0].into(), 100.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(res);
+    let allowance = client
+        .allowance(USER_ID[0].into(), ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(allowance, 0.into());
+    let balance = client
+        .balance_of(USER_ID[0].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 800.into());
+    let total_supply = client.total_supply().recv(extended_vft_id).await.unwrap();
+    assert_eq!(total_supply, 800.into());
+}
+
+#[tokio::test]
+async fn test_transfer_batch() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space);
+    // mint
+    client
+        .mint(ADMIN_ID.into(), 1_000.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // batch exceeding balance fails as a whole
+    let res = client
+        .transfer_batch(vec![
+            (USER_ID[0].into(), 600.into()),
+            (USER_ID[1].into(), 600.into()),
+        ])
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Err(Error::InsufficientBalance));
+
+    // transfer batch
+    let res = client
+        .transfer_batch(vec![
+            (USER_ID[0].into(), 300.into()),
+            (USER_ID[1].into(), 200.into()),
+        ])
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(res);
+    let balance = client
+        .balance_of(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 500.into());
+    let balance = client
+        .balance_of(USER_ID[0].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 300.into());
+    let balance = client
+        .balance_of(USER_ID[1].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 200.into());
+}
+
+fn blake2b_256(data: &[u8]) -> [u8; 32] {
+    use blake2::{digest::consts::U32, Blake2b, Digest};
+    Blake2b::<U32>::digest(data).into()
+}
+
+#[tokio::test]
+async fn test_airdrop() {
+    use sails_rs::{ActorId, Encode, U256};
+
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+    system.mint_to(USER_ID[0], 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space);
+    // mint
+    client
+        .mint(ADMIN_ID.into(), 1_000.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // tree of two leaves
+    let leaves = [
+        blake2b_256(&(0u32, ActorId::from(USER_ID[0]), U256::from(100)).encode()),
+        blake2b_256(&(1u32, ActorId::from(USER_ID[1]), U256::from(200)).encode()),
+    ];
+    let (left, right) = if leaves[0] <= leaves[1] {
+        (leaves[0], leaves[1])
+    } else {
+        (leaves[1], leaves[0])
+    };
+    let root = blake2b_256(&[left, right].concat());
+
+    // create campaign
+    let campaign = client
+        .create_campaign(root, 300.into(), u64::MAX)
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let balance = client
+        .balance_of(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 700.into());
+
+    // claim with invalid amount
+    let res = client
+        .claim(campaign, 0, 200.into(), vec![leaves[1]])
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Err(ExtendedError::InvalidProof));
+
+    // claim
+    client
+        .claim(campaign, 0, 100.into(), vec![leaves[1]])
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let balance = client
+        .balance_of(USER_ID[0].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 100.into());
+    let claimed = client
+        .is_claimed(campaign, 0)
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert!(claimed);
+
+    // claim twice
+    let res = client
+        .claim(campaign, 0, 100.into(), vec![leaves[1]])
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Err(ExtendedError::AlreadyClaimed));
+
+    // reclaim before expiry
+    let res = client
+        .reclaim(campaign)
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Err(ExtendedError::CampaignNotExpired));
+}
+
+#[tokio::test]
+async fn test_vesting() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+    system.mint_to(USER_ID[0], 1_000_000_000_000_000);
+    system.mint_to(USER_ID[1], 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space);
+    // mint
+    client
+        .mint(ADMIN_ID.into(), 1_000.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // invalid schedule leaves the funds with the funder
+    let res = client
+        .create_vesting(USER_ID[0].into(), 300.into(), 0, 2, 1, false)
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Err(ExtendedError::InvalidSchedule));
+    let balance = client
+        .balance_of(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 1_000.into());
+
+    // schedule already vested
+    let vested = client
+        .create_vesting(USER_ID[0].into(), 300.into(), 0, 0, 1, false)
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    // schedule starting in the future
+    let unvested = client
+        .create_vesting(USER_ID[1].into(), 200.into(), u64::MAX / 2, 0, 1_000, true)
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let balance = client
+        .balance_of(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 500.into());
+
+    let releasable = client
+        .releasable_amount(vested)
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(releasable, 300.into());
+    let releasable = client
+        .releasable_amount(unvested)
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(releasable, 0.into());
+
+    // release by anyone goes to beneficiary
+    let released = client
+        .release(vested)
+        .with_args(|args| args.with_actor_id(USER_ID[1].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(released, 300.into());
+    let balance = client
+        .balance_of(USER_ID[0].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 300.into());
+
+    // revoke non-revocable
+    let res = client
+        .revoke_vesting(vested)
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Err(ExtendedError::NotRevocable));
+
+    // revoke by not admin
+    let res = client
+        .revoke_vesting(unvested)
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await;
+    assert!(res.is_err());
+
+    // revoke
+    let revoked = client
+        .revoke_vesting(unvested)
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(revoked, 200.into());
+    let balance = client
+        .balance_of(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 700.into());
+}
+
+#[tokio::test]
+async fn test_hold() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+    system.mint_to(USER_ID[0], 1_000_000_000_000_000);
+    system.mint_to(USER_ID[1], 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space);
+    // mint
+    client
+        .mint(USER_ID[0].into(), 1_000.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // hold with USER_ID[1] as notary
+    let res = client
+        .hold(1.into(), USER_ID[1].into(), 600.into(), 0)
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(res);
+    let balance = client
+        .balance_of(USER_ID[0].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 1_000.into());
+    let spendable = client
+        .spendable_balance_of(USER_ID[0].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(spendable, 400.into());
+    let on_hold = client
+        .balance_on_hold(USER_ID[0].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(on_hold, 600.into());
+
+    // held value can't be transferred
+    let res = client
+        .transfer(ADMIN_ID.into(), 500.into())
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Err(Error::InsufficientBalance));
+
+    // execute by not notary
+    let res = client
+        .execute_hold(1.into(), ADMIN_ID.into())
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Err(Error::NotNotary));
+
+    // execute
+    client
+        .execute_hold(1.into(), ADMIN_ID.into())
+        .with_args(|args| args.with_actor_id(USER_ID[1].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let balance = client
+        .balance_of(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 600.into());
+
+    // hold and release
+    client
+        .hold(2.into(), USER_ID[1].into(), 400.into(), 0)
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    client
+        .release_hold(2.into())
+        .with_args(|args| args.with_actor_id(USER_ID[1].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let spendable = client
+        .spendable_balance_of(USER_ID[0].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(spendable, 400.into());
+}
+
+#[tokio::test]
+async fn test_flash_loan() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+    system.mint_to(USER_ID[0], 1_000_000_000_000_000);
+    system.mint_to(USER_ID[1], 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+    // a program without `FlashBorrower` service to borrow tokens
+    let receiver_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "456")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space.clone());
+
+    // loans are disabled by default
+    let max_loan = client.max_flash_loan().recv(extended_vft_id).await.unwrap();
+    assert_eq!(max_loan, 0.into());
+    let res = client
+        .flash_loan(receiver_id, 100.into(), vec![])
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Err(ExtendedError::FlashLoanExceeded));
+
+    // set config by not admin
+    let res = client
+        .set_flash_loan_config(100, 1_000.into())
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await;
+    assert!(res.is_err());
+
+    // set config
+    client
+        .set_flash_loan_config(100, 1_000.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let fee = client
+        .flash_fee(500.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(fee, 5.into());
+
+    // loan over max
+    let res = client
+        .flash_loan(receiver_id, 1_001.into(), vec![])
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Err(ExtendedError::FlashLoanExceeded));
+
+    // the fee is paid by the initiator even if the receiver rejects the loan
+    let res = client
+        .flash_loan(receiver_id, 500.into(), vec![])
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Err(ExtendedError::Underflow));
+    client
+        .mint(ADMIN_ID.into(), 100.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let res = client
+        .flash_loan(receiver_id, 500.into(), vec![])
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!res);
+    let balance = client
+        .balance_of(receiver_id)
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 0.into());
+    let balance = client
+        .balance_of(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 95.into());
+    let total_supply = client.total_supply().recv(extended_vft_id).await.unwrap();
+    assert_eq!(total_supply, 95.into());
+
+    // the loan can't be moved away before the receiver replies, and is left
+    // out of votes and snapshots
+    client
+        .mint(USER_ID[1].into(), 10.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    client
+        .delegate(USER_ID[1].into())
+        .with_args(|args| args.with_actor_id(USER_ID[1].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    let program_space = program_space.with_block_run_mode(BlockRunMode::Manual);
+    let mut client = VftClient::new(program_space.clone());
+    let mut borrower = VftClient::new(program_space.clone());
+    let mut admin = VftClient::new(program_space.clone());
+    let loan = client
+        .flash_loan(USER_ID[1].into(), 500.into(), vec![])
+        .send(extended_vft_id)
+        .await
+        .unwrap();
+    let transfer = borrower
+        .transfer(USER_ID[0].into(), 500.into())
+        .with_args(|args| args.with_actor_id(USER_ID[1].into()))
+        .send(extended_vft_id)
+        .await
+        .unwrap();
+    let snapshot = admin.snapshot().send(extended_vft_id).await.unwrap();
+    program_space.run_next_block();
+    assert_eq!(
+        transfer.recv().await.unwrap(),
+        Err(Error::InsufficientBalance)
+    );
+    let snapshot_id = snapshot.recv().await.unwrap().unwrap();
+    let balance = borrower
+        .balance_of(USER_ID[1].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 510.into());
+    let votes = borrower
+        .get_votes(USER_ID[1].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(votes, 10.into());
+    reply_from_mailbox(
+        &program_space,
+        extended_vft_id,
+        USER_ID[1],
+        ("FlashBorrower", "OnFlashLoan", true),
+    );
+    assert_eq!(loan.recv().await.unwrap(), Ok(true));
+
+    // the loan is burned back and the fee paid
+    let balance = client
+        .balance_of(USER_ID[1].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 10.into());
+    let balance = client
+        .balance_of(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 90.into());
+    let total_supply = client.total_supply().recv(extended_vft_id).await.unwrap();
+    assert_eq!(total_supply, 100.into());
+    let balance = client
+        .balance_of_at(USER_ID[1].into(), snapshot_id)
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 10.into());
+    let total_supply = client
+        .total_supply_at(snapshot_id)
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(total_supply, 100.into());
+}
+
+#[tokio::test]
+async fn test_transfer_fee() {
+    const TREASURY_ID: u64 = 13;
+
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+    system.mint_to(USER_ID[0], 1_000_000_000_000_000);
+    system.mint_to(USER_ID[1], 1_000_000_000_000_000);
+    let receiver_id = mock_receiver(&system, ("VftReceiver", "OnVftReceived", true));
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space.clone());
+    // mint
+    client
+        .mint(USER_ID[0].into(), 2_000.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // set fee by not admin
+    let res = client
+        .set_transfer_fee(100, TREASURY_ID.into())
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await;
+    assert!(res.is_err());
+
+    // set 1% fee
+    client
+        .set_transfer_fee(100, TREASURY_ID.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // transfer is charged
+    client
+        .transfer(USER_ID[1].into(), 500.into())
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let balance = client
+        .balance_of(USER_ID[1].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 495.into());
+    let balance = client
+        .balance_of(TREASURY_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 5.into());
+
+    // transfer to exempt account is free
+    client
+        .set_fee_exempt(ADMIN_ID.into(), true)
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    client
+        .transfer(ADMIN_ID.into(), 500.into())
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let balance = client
+        .balance_of(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 500.into());
+
+    // transfer from is charged
+    client
+        .approve(USER_ID[1].into(), 100.into())
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    client
+        .transfer_from(USER_ID[0].into(), USER_ID[1].into(), 100.into())
+        .with_args(|args| args.with_actor_id(USER_ID[1].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let balance = client
+        .balance_of(USER_ID[1].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 594.into());
+
+    // transfer batch is charged for every recipient
+    client
+        .transfer_batch(vec![
+            (USER_ID[1].into(), 100.into()),
+            (receiver_id, 200.into()),
+        ])
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let balance = client
+        .balance_of(USER_ID[1].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 693.into());
+    let balance = client
+        .balance_of(receiver_id)
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 198.into());
+
+    // accepted transfer and call is charged
+    let res = client
+        .transfer_and_call(receiver_id, 100.into(), vec![])
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(res);
+    let balance = client
+        .balance_of(receiver_id)
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 297.into());
+
+    // executed hold is charged
+    client
+        .hold(1.into(), USER_ID[1].into(), 100.into(), 0)
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    client
+        .execute_hold(1.into(), USER_ID[1].into())
+        .with_args(|args| args.with_actor_id(USER_ID[1].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let balance = client
+        .balance_of(USER_ID[1].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 792.into());
+    let balance = client
+        .balance_of(TREASURY_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 11.into());
+
+    // total supply is unchanged
+    let total_supply = client.total_supply().recv(extended_vft_id).await.unwrap();
+    assert_eq!(total_supply, 2_000.into());
+
+    // receiver is told the value it's left with once charged
+    let program_space = program_space.with_block_run_mode(BlockRunMode::Manual);
+    let mut client = VftClient::new(program_space.clone());
+    let transfer = client
+        .transfer_and_call(USER_ID[1].into(), 100.into(), vec![])
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send(extended_vft_id)
+        .await
+        .unwrap();
+    program_space.run_next_block();
+    let payload = (
+        "VftReceiver",
+        "OnVftReceived",
+        ActorId::from(USER_ID[0]),
+        U256::from(99),
+        Vec::<u8>::new(),
+    );
+    let log = Log::builder()
+        .source(extended_vft_id)
+        .dest(USER_ID[1])
+        .payload(payload);
+    assert!(program_space
+        .system()
+        .get_mailbox(USER_ID[1])
+        .contains(&log));
+    reply_from_mailbox(
+        &program_space,
+        extended_vft_id,
+        USER_ID[1],
+        ("VftReceiver", "OnVftReceived", true),
+    );
+    assert!(transfer.recv().await.unwrap().unwrap());
+    let balance = VftClient::new(program_space.clone())
+        .balance_of(USER_ID[1].into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 891.into());
+    let balance = VftClient::new(program_space)
+        .balance_of(TREASURY_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 12.into());
+}
+
+#[tokio::test]
+async fn test_holders() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+    system.mint_to(USER_ID[0], 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space);
+    // mint
+    client
+        .mint(USER_ID[1].into(), 1_000.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    client
+        .mint(USER_ID[0].into(), 1_000.into())
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let count = client.holders_count().recv(extended_vft_id).await.unwrap();
+    assert_eq!(count, 2);
+    let holders = client.holders(0, 10).recv(extended_vft_id).await.unwrap();
+    assert_eq!(
+        holders,
+        vec![
+            (USER_ID[0].into(), 1_000.into()),
+            (USER_ID[1].into(), 1_000.into())
+        ]
+    );
+
+    // holder is removed once balance is zero
+    client
+        .transfer(USER_ID[1].into(), 1_000.into())
+        .with_args(|args| args.with_actor_id(USER_ID[0].into()))
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let holders = client.holders(0, 10).recv(extended_vft_id).await.unwrap();
+    assert_eq!(holders, vec![(USER_ID[1].into(), 2_000.into())]);
+}
+
+#[tokio::test]
+async fn test_allowances_of() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vft.opt.wasm");
+
+    let extended_vft_factory = Factory::new(program_space.clone());
+    let extended_vft_id = extended_vft_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space);
+    // approve
+    for (spender, value) in [(USER_ID[1], 200), (USER_ID[0], 100)] {
+        client
+            .approve(spender.into(), value.into())
+            .send_recv(extended_vft_id)
+            .await
+            .unwrap();
+    }
+
+    let count = client
+        .spenders_count(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+    let allowances = client
+        .allowances_of(ADMIN_ID.into(), 0, 10)
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        allowances,
+        vec![
+            (USER_ID[0].into(), 100.into()),
+            (USER_ID[1].into(), 200.into())
+        ]
+    );
+
+    // revoke all
+    let res = client
+        .revoke_all_allowances()
+        .send_recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert!(res);
+    let count = client
+        .spenders_count(ADMIN_ID.into())
+        .recv(extended_vft_id)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+}
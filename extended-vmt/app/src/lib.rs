@@ -13,6 +13,13 @@ impl ExtendedVmtProgram {
         Self(())
     }
 
+    /// Same as `new`, but the inherited methods panic on errors instead of
+    /// returning them, for callers relying on error replies.
+    pub fn new_with_panicking_errors(name: String, symbol: String, decimals: u8) -> Self {
+        ExtendedService::init(name, symbol, decimals).with_panicking_errors();
+        Self(())
+    }
+
     pub fn vmt(&self) -> ExtendedService {
         ExtendedService::new()
     }
@@ -1,4 +1,4 @@
-use super::utils::{Error, Result, TokenId, TokenMetadata};
+use super::utils::{Error, Result, TokenId, TokenInfo, TokenKind, TokenMetadata};
 use crate::services::extended_vmt::{Event, ExtendedStorage};
 use sails_rs::{
     collections::{HashMap, HashSet},
@@ -29,7 +29,26 @@ pub fn mint(
     }
 
     ids.iter().enumerate().try_for_each(|(i, id)| {
-        if storage.token_metadata.contains_key(id) {
+        if let Some(info) = storage.tokens.get(id) {
+            // Metadata of registered tokens is set once, by `create_token`.
+            if meta[i].is_some() {
+                return Err(Error::TokenAlreadyExists);
+            }
+
+            // Burned tokens don't give their supply back, so that a
+            // non-fungible token can't be minted again once burned.
+            let minted = info
+                .minted
+                .checked_add(amounts[i])
+                .ok_or(Error::MaxSupplyExceeded)?;
+
+            if info
+                .max_supply
+                .is_some_and(|max_supply| minted > max_supply)
+            {
+                return Err(Error::MaxSupplyExceeded);
+            }
+        } else if storage.token_metadata.contains_key(id) {
             return Err(Error::TokenAlreadyExists);
         } else if let Some(_token_meta) = &meta[i] {
             if amounts[i] > U256::one() {
@@ -49,6 +68,9 @@ pub fn mint(
                 *quantity = quantity.saturating_add(*amount);
             })
             .or_insert(*amount);
+        if let Some(info) = storage.tokens.get_mut(id) {
+            info.minted = info.minted.saturating_add(*amount);
+        }
     }
 
     Ok(Event::Minted { to, ids, amounts })
@@ -65,7 +87,14 @@ fn mint_impl(
     if let Some(metadata) = meta {
         storage.token_metadata.insert(*id, metadata);
         // since we have metadata = means we have an nft, so add it to the owners
-        storage.owners.insert(*id, *account);
+        set_owner(storage, *id, Some(*account));
+    } else if !amount.is_zero()
+        && storage
+            .tokens
+            .get(id)
+            .is_some_and(|info| info.kind == TokenKind::NonFungible)
+    {
+        set_owner(storage, *id, Some(*account));
     }
 
     balances
@@ -93,7 +122,7 @@ pub fn burn(
     ids.iter()
         .zip(amounts.clone())
         .try_for_each(|(id, amount)| {
-            if storage.token_metadata.contains_key(id) && amount > U256::one() {
+            if is_non_fungible(storage, id) && amount > U256::one() {
                 return Err(Error::AmountGreaterThanOneForNft);
             }
             check_opportunity_burn(balances, &from, id, amount)
@@ -111,6 +140,239 @@ pub fn burn(
     Ok(Event::Burned { from, ids, amounts })
 }
 
+/// Registers token `id` of `kind`, so that no more than `max_supply` of it
+/// can ever be minted, burned tokens included. Non-fungible tokens have a
+/// max supply of one.
+pub fn create_token(
+    storage: &mut ExtendedStorage,
+    total_supply: &HashMap<TokenId, U256>,
+    id: TokenId,
+    kind: TokenKind,
+    max_supply: Option<U256>,
+    metadata: Option<TokenMetadata>,
+    creator: ActorId,
+) -> Result<Option<U256>> {
+    if storage.tokens.contains_key(&id)
+        || storage.token_metadata.contains_key(&id)
+        || total_supply.contains_key(&id)
+    {
+        return Err(Error::TokenAlreadyExists);
+    }
+
+    let max_supply = match (kind, max_supply) {
+        (_, Some(max_supply)) if max_supply.is_zero() => return Err(Error::InvalidMaxSupply),
+        (TokenKind::NonFungible, Some(max_supply)) if max_supply > U256::one() => {
+            return Err(Error::InvalidMaxSupply)
+        }
+        (TokenKind::NonFungible, _) => Some(U256::one()),
+        (_, max_supply) => max_supply,
+    };
+
+    if kind == TokenKind::Fungible && metadata.is_some() {
+        return Err(Error::MintMetadataToFungibleToken);
+    }
+
+    if let Some(metadata) = metadata {
+        storage.token_metadata.insert(id, metadata);
+    }
+
+    storage.tokens.insert(
+        id,
+        TokenInfo {
+            kind,
+            max_supply,
+            minted: U256::zero(),
+            creator,
+        },
+    );
+
+    Ok(max_supply)
+}
+
+/// Returns the kind of token `id`, which for tokens minted without
+/// `create_token` is told by whether they have metadata.
+pub fn token_kind(
+    storage: &ExtendedStorage,
+    total_supply: &HashMap<TokenId, U256>,
+    id: &TokenId,
+) -> Option<TokenKind> {
+    if let Some(info) = storage.tokens.get(id) {
+        Some(info.kind)
+    } else if storage.token_metadata.contains_key(id) {
+        Some(TokenKind::NonFungible)
+    } else if total_supply.contains_key(id) {
+        Some(TokenKind::Fungible)
+    } else {
+        None
+    }
+}
+
+/// Returns how much more of token `id` can be minted, `None` meaning there
+/// is no limit.
+pub fn remaining_supply(storage: &ExtendedStorage, id: &TokenId) -> Option<U256> {
+    match storage.tokens.get(id) {
+        Some(info) => info
+            .max_supply
+            .map(|max_supply| max_supply.saturating_sub(info.minted)),
+        // Tokens minted with metadata can't be minted again.
+        None if storage.token_metadata.contains_key(id) => Some(U256::zero()),
+        None => None,
+    }
+}
+
+/// Returns the URI of token `id`: its metadata `reference` if set, else the
+/// base URI with `{id}` replaced by the id as 64 lowercase hex digits, as
+/// ERC-1155 defines.
+pub fn uri(storage: &ExtendedStorage, id: &TokenId) -> String {
+    if let Some(reference) = storage
+        .token_metadata
+        .get(id)
+        .and_then(|metadata| metadata.reference.clone())
+    {
+        return reference;
+    }
+
+    let [w0, w1, w2, w3] = id.0;
+    storage
+        .base_uri
+        .replace("{id}", &format!("{w3:016x}{w2:016x}{w1:016x}{w0:016x}"))
+}
+
+/// Returns a `Uri` event for every token given metadata with a `reference`.
+pub fn uri_events(ids: &[TokenId], meta: &[Option<TokenMetadata>]) -> Vec<Event> {
+    ids.iter()
+        .zip(meta)
+        .filter_map(|(id, meta)| {
+            let value = meta.as_ref()?.reference.clone()?;
+            Some(Event::Uri {
+                value,
+                id: Some(*id),
+            })
+        })
+        .collect()
+}
+
+/// Records `to` as the owner of the non-fungible tokens among `ids` it was
+/// transferred.
+pub fn transfer_ownership(
+    storage: &mut ExtendedStorage,
+    to: ActorId,
+    ids: &[TokenId],
+    amounts: &[U256],
+) {
+    for (id, amount) in ids.iter().zip(amounts) {
+        if !amount.is_zero() && is_non_fungible(storage, id) {
+            set_owner(storage, *id, Some(to));
+        }
+    }
+}
+
+/// Returns a page of the non-fungible tokens of `owner`, ordered by id.
+pub fn tokens_for_owner(
+    storage: &ExtendedStorage,
+    owner: &ActorId,
+    offset: u32,
+    limit: u32,
+) -> Vec<TokenId> {
+    storage
+        .owned_tokens
+        .get(owner)
+        .map(|ids| {
+            ids.iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .copied()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns a page of the ids of tokens minted or registered, ordered by id.
+pub fn token_ids(
+    storage: &ExtendedStorage,
+    total_supply: &HashMap<TokenId, U256>,
+    offset: u32,
+    limit: u32,
+) -> Vec<TokenId> {
+    let mut ids: Vec<_> = total_supply
+        .keys()
+        .chain(storage.tokens.keys())
+        .copied()
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+
+    ids.into_iter()
+        .skip(offset as usize)
+        .take(limit as usize)
+        .collect()
+}
+
+/// Makes `owner` the owner of token `id`, keeping the index of tokens by
+/// owner in sync.
+fn set_owner(storage: &mut ExtendedStorage, id: TokenId, owner: Option<ActorId>) {
+    let previous = match owner {
+        Some(owner) => storage.owners.insert(id, owner),
+        None => storage.owners.remove(&id),
+    };
+
+    if let Some(previous) = previous {
+        if let Some(ids) = storage.owned_tokens.get_mut(&previous) {
+            ids.remove(&id);
+
+            if ids.is_empty() {
+                storage.owned_tokens.remove(&previous);
+            }
+        }
+    }
+
+    if let Some(owner) = owner {
+        storage.owned_tokens.entry(owner).or_default().insert(id);
+    }
+}
+
+fn is_non_fungible(storage: &ExtendedStorage, id: &TokenId) -> bool {
+    match storage.tokens.get(id) {
+        Some(info) => info.kind == TokenKind::NonFungible,
+        None => storage.token_metadata.contains_key(id),
+    }
+}
+
+/// Undoes a `mint` of `ids` and `amounts` to `to`, along with the metadata
+/// it stored and the ids no one holds anymore. Fails if `to` doesn't hold the tokens anymore.
+pub fn revert_mint(
+    balances: &mut HashMap<TokenId, HashMap<ActorId, U256>>,
+    total_supply: &mut HashMap<TokenId, U256>,
+    storage: &mut ExtendedStorage,
+    to: ActorId,
+    ids: Vec<TokenId>,
+    amounts: Vec<U256>,
+) -> Result<()> {
+    burn(
+        balances,
+        total_supply,
+        storage,
+        to,
+        ids.clone(),
+        amounts.clone(),
+    )?;
+
+    for (id, amount) in ids.into_iter().zip(amounts) {
+        // The mint never happened, so its supply is given back.
+        if let Some(info) = storage.tokens.get_mut(&id) {
+            info.minted = info.minted.saturating_sub(amount);
+        } else {
+            storage.token_metadata.remove(&id);
+
+            if total_supply.get(&id).is_some_and(|supply| supply.is_zero()) {
+                total_supply.remove(&id);
+            }
+        }
+    }
+
+    Ok(())
+}
+
 fn check_opportunity_burn(
     balances: &mut HashMap<TokenId, HashMap<ActorId, U256>>,
     owner: &ActorId,
@@ -132,7 +394,9 @@ fn burn_impl(
     id: &TokenId,
     amount: U256,
 ) {
-    storage.owners.remove(id);
+    if !amount.is_zero() {
+        set_owner(storage, *id, None);
+    }
     balances
         .entry(*id)
         .or_default()
@@ -267,6 +531,253 @@ mod tests {
         assert_eq!(result, Err(Error::IdIsNotUnique));
     }
 
+    #[test]
+    fn test_create_token() {
+        let mut storage = ExtendedStorage::default();
+        let total_supply = HashMap::from([(2u64.into(), U256::one())]);
+
+        // Non-fungible tokens are bounded to one.
+        assert_eq!(
+            create_token(
+                &mut storage,
+                &total_supply,
+                token_id(),
+                TokenKind::NonFungible,
+                Some(2.into()),
+                None,
+                actor_id()
+            ),
+            Err(Error::InvalidMaxSupply)
+        );
+        assert_eq!(
+            create_token(
+                &mut storage,
+                &total_supply,
+                token_id(),
+                TokenKind::NonFungible,
+                None,
+                Some(token_metadata()),
+                actor_id()
+            ),
+            Ok(Some(U256::one()))
+        );
+        assert_eq!(
+            token_kind(&storage, &total_supply, &token_id()),
+            Some(TokenKind::NonFungible)
+        );
+
+        // Existing tokens can't be registered, fungible ones have no metadata.
+        assert_eq!(
+            create_token(
+                &mut storage,
+                &total_supply,
+                2u64.into(),
+                TokenKind::Fungible,
+                None,
+                None,
+                actor_id()
+            ),
+            Err(Error::TokenAlreadyExists)
+        );
+        assert_eq!(
+            create_token(
+                &mut storage,
+                &total_supply,
+                3u64.into(),
+                TokenKind::Fungible,
+                None,
+                Some(token_metadata()),
+                actor_id()
+            ),
+            Err(Error::MintMetadataToFungibleToken)
+        );
+        assert_eq!(
+            token_kind(&storage, &total_supply, &2u64.into()),
+            Some(TokenKind::Fungible)
+        );
+        assert_eq!(token_kind(&storage, &total_supply, &3u64.into()), None);
+    }
+
+    #[test]
+    fn test_mint_registered_token() {
+        let mut balances = HashMap::new();
+        let mut total_supply = HashMap::new();
+        let mut storage = ExtendedStorage::default();
+
+        create_token(
+            &mut storage,
+            &total_supply,
+            token_id(),
+            TokenKind::SemiFungible,
+            Some(10.into()),
+            Some(token_metadata()),
+            actor_id(),
+        )
+        .unwrap();
+        assert_eq!(remaining_supply(&storage, &token_id()), Some(10.into()));
+
+        // Metadata is already set.
+        assert_eq!(
+            mint(
+                &mut balances,
+                &mut total_supply,
+                &mut storage,
+                actor_id(),
+                vec![token_id()],
+                vec![1.into()],
+                vec![Some(token_metadata())],
+            ),
+            Err(Error::TokenAlreadyExists)
+        );
+
+        // Semi-fungible tokens can be minted more than once, up to max supply.
+        for _ in 0..2 {
+            assert!(mint(
+                &mut balances,
+                &mut total_supply,
+                &mut storage,
+                actor_id(),
+                vec![token_id()],
+                vec![4.into()],
+                vec![None],
+            )
+            .is_ok());
+        }
+        assert_eq!(remaining_supply(&storage, &token_id()), Some(2.into()));
+        assert_eq!(
+            mint(
+                &mut balances,
+                &mut total_supply,
+                &mut storage,
+                actor_id(),
+                vec![token_id()],
+                vec![3.into()],
+                vec![None],
+            ),
+            Err(Error::MaxSupplyExceeded)
+        );
+
+        // Burning more than one is allowed, though the token has metadata,
+        // but doesn't allow minting more.
+        assert!(burn(
+            &mut balances,
+            &mut total_supply,
+            &mut storage,
+            actor_id(),
+            vec![token_id()],
+            vec![8.into()],
+        )
+        .is_ok());
+        assert_eq!(remaining_supply(&storage, &token_id()), Some(2.into()));
+        assert_eq!(
+            mint(
+                &mut balances,
+                &mut total_supply,
+                &mut storage,
+                actor_id(),
+                vec![token_id()],
+                vec![3.into()],
+                vec![None],
+            ),
+            Err(Error::MaxSupplyExceeded)
+        );
+    }
+
+    #[test]
+    fn test_uri() {
+        let mut storage = ExtendedStorage {
+            base_uri: "https://example.com/{id}.json".into(),
+            ..Default::default()
+        };
+        storage.token_metadata.insert(
+            2u64.into(),
+            TokenMetadata {
+                reference: Some("ipfs://token".into()),
+                ..token_metadata()
+            },
+        );
+
+        assert_eq!(
+            uri(&storage, &U256::from(0x4cce)),
+            "https://example.com/0000000000000000000000000000000000000000000000000000000000004cce.json"
+        );
+        assert_eq!(
+            uri(&storage, &U256::MAX),
+            format!("https://example.com/{}.json", "f".repeat(64))
+        );
+        assert_eq!(uri(&storage, &2u64.into()), "ipfs://token");
+        assert_eq!(
+            uri_events(
+                &[1u64.into(), 2u64.into()],
+                &[
+                    Some(token_metadata()),
+                    storage.token_metadata.get(&U256::from(2)).cloned()
+                ]
+            ),
+            vec![Event::Uri {
+                value: "ipfs://token".into(),
+                id: Some(2u64.into())
+            }]
+        );
+    }
+
+    #[test]
+    fn test_tokens_for_owner() {
+        let mut balances = HashMap::new();
+        let mut total_supply = HashMap::new();
+        let mut storage = ExtendedStorage::default();
+        let bob: ActorId = 2u64.into();
+        let ids: Vec<TokenId> = vec![3u64.into(), 1u64.into(), 2u64.into()];
+
+        mint(
+            &mut balances,
+            &mut total_supply,
+            &mut storage,
+            actor_id(),
+            ids.clone(),
+            vec![U256::one(); 3],
+            vec![Some(token_metadata()), Some(token_metadata()), None],
+        )
+        .unwrap();
+
+        // Only non-fungible tokens are indexed, ordered by id.
+        assert_eq!(
+            tokens_for_owner(&storage, &actor_id(), 0, 10),
+            vec![1u64.into(), 3u64.into()]
+        );
+        assert_eq!(
+            token_ids(&storage, &total_supply, 1, 10),
+            vec![2u64.into(), 3u64.into()]
+        );
+
+        // Transfers move tokens between owners.
+        transfer_ownership(
+            &mut storage,
+            bob,
+            &ids,
+            &[U256::one(), U256::zero(), U256::one()],
+        );
+        assert_eq!(
+            tokens_for_owner(&storage, &actor_id(), 0, 10),
+            vec![1u64.into()]
+        );
+        assert_eq!(tokens_for_owner(&storage, &bob, 0, 10), vec![3u64.into()]);
+        assert_eq!(storage.owners.get(&U256::from(3)), Some(&bob));
+
+        // Burnt tokens have no owner.
+        burn(
+            &mut balances,
+            &mut total_supply,
+            &mut storage,
+            actor_id(),
+            vec![1u64.into()],
+            vec![U256::one()],
+        )
+        .unwrap();
+        assert!(tokens_for_owner(&storage, &actor_id(), 0, 10).is_empty());
+        assert!(!storage.owned_tokens.contains_key(&actor_id()));
+    }
+
     #[test]
     fn test_burn_success() {
         let mut balances: HashMap<U256, HashMap<ActorId, U256>> = HashMap::new();
@@ -1,22 +1,26 @@
 #![allow(static_mut_refs)]
+use core::slice;
 use sails_rs::{
-    collections::{HashMap, HashSet},
-    gstd::{msg, service},
+    collections::{BTreeSet, HashMap, HashSet},
+    gstd::{exec, msg, service},
     prelude::*,
 };
 mod funcs;
-use crate::services;
 pub mod utils;
 use utils::*;
-use vmt_service::{Service as VmtService, Storage};
+use vmt_service::{checked, Hooks, Service as VmtService, Storage};
 
 #[derive(Default)]
 pub struct ExtendedStorage {
     token_metadata: HashMap<TokenId, TokenMetadata>,
     owners: HashMap<TokenId, ActorId>,
+    owned_tokens: HashMap<ActorId, BTreeSet<TokenId>>,
+    tokens: HashMap<TokenId, TokenInfo>,
+    base_uri: String,
     minters: HashSet<ActorId>,
     burners: HashSet<ActorId>,
     admins: HashSet<ActorId>,
+    paused: bool,
 }
 
 static mut EXTENDED_STORAGE: Option<ExtendedStorage> = None;
@@ -36,6 +40,19 @@ pub enum Event {
         ids: Vec<TokenId>,
         amounts: Vec<U256>,
     },
+    TokenCreated {
+        id: TokenId,
+        kind: TokenKind,
+        max_supply: Option<U256>,
+        creator: ActorId,
+    },
+    /// URI of token `id` changed, or the base URI if `id` is `None`.
+    Uri {
+        value: String,
+        id: Option<TokenId>,
+    },
+    Paused,
+    Unpaused,
 }
 #[derive(Clone)]
 pub struct ExtendedService {
@@ -54,13 +71,24 @@ impl ExtendedService {
             EXTENDED_STORAGE = Some(ExtendedStorage {
                 token_metadata: HashMap::new(),
                 owners: HashMap::new(),
+                owned_tokens: HashMap::new(),
+                tokens: HashMap::new(),
+                base_uri: String::new(),
                 admins: [admin].into(),
                 minters: [admin].into(),
                 burners: [admin].into(),
+                paused: false,
             });
         };
         ExtendedService {
-            vmt: <VmtService>::init(name, symbol, decimals),
+            vmt: <VmtService>::init(name, symbol, decimals).with_hooks(&ExtendedHooks),
+        }
+    }
+
+    /// Makes the inherited methods panic on errors instead of returning them.
+    pub fn with_panicking_errors(self) -> Self {
+        ExtendedService {
+            vmt: self.vmt.with_panicking_errors(),
         }
     }
 
@@ -88,6 +116,51 @@ impl From<ExtendedService> for VmtService {
 
 #[service(extends = VmtService, events = Event)]
 impl ExtendedService {
+    /// Registers token `id` of `kind`, bounding the amount ever minted of it with `mint`
+    /// or `mint_batch` by `max_supply`: burning doesn't free supply, so a burned
+    /// non-fungible token can't be minted again. Metadata of a registered token is set
+    /// here rather than on mint.
+    #[export]
+    pub fn create_token(
+        &mut self,
+        id: TokenId,
+        kind: TokenKind,
+        max_supply: Option<U256>,
+        metadata: Option<TokenMetadata>,
+        creator: ActorId,
+    ) -> utils::Result<()> {
+        if !self.get().minters.contains(&msg::source()) {
+            panic!("Not allowed to mint")
+        };
+        self.ensure_not_paused();
+
+        let uri_events = funcs::uri_events(&[id], slice::from_ref(&metadata));
+        let max_supply = checked(|| {
+            funcs::create_token(
+                self.get_mut(),
+                Storage::total_supply(),
+                id,
+                kind,
+                max_supply,
+                metadata,
+                creator,
+            )
+        })?;
+        self.emit_event(Event::TokenCreated {
+            id,
+            kind,
+            max_supply,
+            creator,
+        })
+        .expect("Notification Error");
+
+        for event in uri_events {
+            self.emit_event(event).expect("Notification Error");
+        }
+
+        Ok(())
+    }
+
     #[export]
     pub fn mint(
         &mut self,
@@ -95,12 +168,14 @@ impl ExtendedService {
         id: TokenId,
         amount: U256,
         token_metadata: Option<TokenMetadata>,
-    ) {
+    ) -> utils::Result<()> {
         if !self.get().minters.contains(&msg::source()) {
             panic!("Not allowed to mint")
         };
+        self.ensure_not_paused();
 
-        let event = services::utils::panicking(|| {
+        let uri_events = funcs::uri_events(&[id], slice::from_ref(&token_metadata));
+        let event = checked(|| {
             funcs::mint(
                 Storage::balances(),
                 Storage::total_supply(),
@@ -110,8 +185,13 @@ impl ExtendedService {
                 vec![amount],
                 vec![token_metadata],
             )
-        });
+        })?;
         self.emit_event(event).expect("Notification Error");
+        for event in uri_events {
+            self.emit_event(event).expect("Notification Error");
+        }
+
+        Ok(())
     }
 
     #[export]
@@ -121,12 +201,14 @@ impl ExtendedService {
         ids: Vec<TokenId>,
         amounts: Vec<U256>,
         token_metadata: Vec<Option<TokenMetadata>>,
-    ) {
+    ) -> utils::Result<()> {
         if !self.get().minters.contains(&msg::source()) {
             panic!("Not allowed to mint")
         };
+        self.ensure_not_paused();
 
-        let event = services::utils::panicking(|| {
+        let uri_events = funcs::uri_events(&ids, &token_metadata);
+        let event = checked(|| {
             funcs::mint(
                 Storage::balances(),
                 Storage::total_supply(),
@@ -136,17 +218,102 @@ impl ExtendedService {
                 amounts,
                 token_metadata,
             )
-        });
+        })?;
+        self.emit_event(event).expect("Notification Error");
+        for event in uri_events {
+            self.emit_event(event).expect("Notification Error");
+        }
+
+        Ok(())
+    }
+
+    /// Mints like `mint`, then calls the
+    /// `VmtReceiver/OnVmtReceived(operator, from, id, amount, data)` hook of `to` if it's
+    /// a program, with a zero `from`. The mint is undone and `false` is returned unless the receiver replies `true`.
+    #[export]
+    pub async fn safe_mint(
+        &mut self,
+        to: ActorId,
+        id: TokenId,
+        amount: U256,
+        token_metadata: Option<TokenMetadata>,
+        data: Vec<u8>,
+    ) -> utils::Result<bool> {
+        let uri_events = funcs::uri_events(&[id], slice::from_ref(&token_metadata));
+        let args = (msg::source(), ActorId::zero(), id, amount, data);
+        let event = self
+            .safe_mint_impl(
+                to,
+                vec![id],
+                vec![amount],
+                vec![token_metadata],
+                vmt_service::utils::RECEIVED_METHOD,
+                args,
+            )
+            .await?;
+
+        let Some(event) = event else {
+            return Ok(false);
+        };
+        self.emit_event(event).expect("Notification Error");
+        for event in uri_events {
+            self.emit_event(event).expect("Notification Error");
+        }
+
+        Ok(true)
+    }
+
+    /// Mints like `mint_batch`, then calls the
+    /// `VmtReceiver/OnVmtBatchReceived(operator, from, ids, amounts, data)` hook of `to`
+    /// if it's a program, with a zero `from`. The mint is undone and `false` is returned unless the receiver
+    /// replies `true`.
+    #[export]
+    pub async fn safe_mint_batch(
+        &mut self,
+        to: ActorId,
+        ids: Vec<TokenId>,
+        amounts: Vec<U256>,
+        token_metadata: Vec<Option<TokenMetadata>>,
+        data: Vec<u8>,
+    ) -> utils::Result<bool> {
+        let uri_events = funcs::uri_events(&ids, &token_metadata);
+        let args = (
+            msg::source(),
+            ActorId::zero(),
+            ids.clone(),
+            amounts.clone(),
+            data,
+        );
+        let event = self
+            .safe_mint_impl(
+                to,
+                ids,
+                amounts,
+                token_metadata,
+                vmt_service::utils::BATCH_RECEIVED_METHOD,
+                args,
+            )
+            .await?;
+
+        let Some(event) = event else {
+            return Ok(false);
+        };
         self.emit_event(event).expect("Notification Error");
+        for event in uri_events {
+            self.emit_event(event).expect("Notification Error");
+        }
+
+        Ok(true)
     }
 
     #[export]
-    pub fn burn(&mut self, from: ActorId, id: TokenId, amount: U256) {
+    pub fn burn(&mut self, from: ActorId, id: TokenId, amount: U256) -> utils::Result<()> {
         if !self.get().burners.contains(&msg::source()) {
             panic!("Not allowed to burn")
         };
+        self.ensure_not_paused();
 
-        let event = services::utils::panicking(|| {
+        let event = checked(|| {
             funcs::burn(
                 Storage::balances(),
                 Storage::total_supply(),
@@ -155,17 +322,25 @@ impl ExtendedService {
                 vec![id],
                 vec![amount],
             )
-        });
+        })?;
         self.emit_event(event).expect("Notification Error");
+
+        Ok(())
     }
 
     #[export]
-    pub fn burn_batch(&mut self, from: ActorId, ids: Vec<TokenId>, amounts: Vec<U256>) {
+    pub fn burn_batch(
+        &mut self,
+        from: ActorId,
+        ids: Vec<TokenId>,
+        amounts: Vec<U256>,
+    ) -> utils::Result<()> {
         if !self.get().burners.contains(&msg::source()) {
             panic!("Not allowed to burn")
         };
+        self.ensure_not_paused();
 
-        let event = services::utils::panicking(|| {
+        let event = checked(|| {
             funcs::burn(
                 Storage::balances(),
                 Storage::total_supply(),
@@ -174,8 +349,69 @@ impl ExtendedService {
                 ids,
                 amounts,
             )
-        });
+        })?;
         self.emit_event(event).expect("Notification Error");
+
+        Ok(())
+    }
+
+    #[export]
+    pub fn pause(&mut self) -> bool {
+        self.ensure_is_admin();
+
+        let mutated = !self.get().paused;
+        if mutated {
+            self.get_mut().paused = true;
+            self.emit_event(Event::Paused).expect("Notification Error");
+        }
+        mutated
+    }
+
+    #[export]
+    pub fn unpause(&mut self) -> bool {
+        self.ensure_is_admin();
+
+        let mutated = self.get().paused;
+        if mutated {
+            self.get_mut().paused = false;
+            self.emit_event(Event::Unpaused)
+                .expect("Notification Error");
+        }
+        mutated
+    }
+
+    /// Sets the URI returned by `uri` for tokens without a metadata `reference`,
+    /// in which `{id}` stands for the token id in hex.
+    #[export]
+    pub fn set_base_uri(&mut self, base_uri: String) -> bool {
+        self.ensure_is_admin();
+
+        let mutated = self.get().base_uri != base_uri;
+        if mutated {
+            self.get_mut().base_uri = base_uri.clone();
+            self.emit_event(Event::Uri {
+                value: base_uri,
+                id: None,
+            })
+            .expect("Notification Error");
+        }
+        mutated
+    }
+
+    #[export]
+    pub fn base_uri(&self) -> String {
+        self.get().base_uri.clone()
+    }
+
+    /// Returns the URI of token `id`, its metadata `reference` if set.
+    #[export]
+    pub fn uri(&self, id: TokenId) -> String {
+        funcs::uri(self.get(), &id)
+    }
+
+    #[export]
+    pub fn paused(&self) -> bool {
+        self.get().paused
     }
 
     #[export]
@@ -214,6 +450,49 @@ impl ExtendedService {
         self.get_mut().burners.remove(&from);
     }
 
+    /// Returns the kind of token `id`, if it exists.
+    #[export]
+    pub fn token_kind(&self, id: TokenId) -> Option<TokenKind> {
+        funcs::token_kind(self.get(), Storage::total_supply(), &id)
+    }
+
+    /// Returns how much more of token `id` can be minted, `None` meaning there is no limit.
+    #[export]
+    pub fn remaining_supply(&self, id: TokenId) -> Option<U256> {
+        funcs::remaining_supply(self.get(), &id)
+    }
+
+    /// Returns the creator of token `id`, if it was registered with `create_token`.
+    #[export]
+    pub fn token_creator(&self, id: TokenId) -> Option<ActorId> {
+        self.get().tokens.get(&id).map(|info| info.creator)
+    }
+
+    #[export]
+    pub fn token_metadata(&self, id: TokenId) -> Option<TokenMetadata> {
+        self.get().token_metadata.get(&id).cloned()
+    }
+
+    /// Returns the owner of non-fungible token `id`.
+    #[export]
+    pub fn owner_of(&self, id: TokenId) -> Option<ActorId> {
+        self.get().owners.get(&id).copied()
+    }
+
+    /// Returns up to `limit` non-fungible tokens of `owner`, skipping the first `offset`.
+    /// Tokens are ordered by id.
+    #[export]
+    pub fn tokens_for_owner(&self, owner: ActorId, offset: u32, limit: u32) -> Vec<TokenId> {
+        funcs::tokens_for_owner(self.get(), &owner, offset, limit)
+    }
+
+    /// Returns up to `limit` ids of tokens minted or registered, skipping the first `offset`.
+    /// Ids are ordered.
+    #[export]
+    pub fn token_ids(&self, offset: u32, limit: u32) -> Vec<TokenId> {
+        funcs::token_ids(self.get(), Storage::total_supply(), offset, limit)
+    }
+
     #[export]
     pub fn minters(&self) -> Vec<ActorId> {
         self.get().minters.clone().into_iter().collect()
@@ -236,4 +515,91 @@ impl ExtendedService {
             panic!("Not admin")
         };
     }
+
+    fn ensure_not_paused(&self) {
+        if self.get().paused {
+            panic!("Paused")
+        };
+    }
+
+    /// Mints to this program, then calls `method` of the receiver hook of `to` with
+    /// `args`. Returns the `Minted` event once the tokens are passed on to `to` if the
+    /// receiver accepts, otherwise undoes the mint. Kept by this program until the
+    /// receiver replies, the tokens can't be moved by `to` meanwhile.
+    async fn safe_mint_impl(
+        &mut self,
+        to: ActorId,
+        ids: Vec<TokenId>,
+        amounts: Vec<U256>,
+        token_metadata: Vec<Option<TokenMetadata>>,
+        method: &str,
+        args: impl Encode,
+    ) -> utils::Result<Option<Event>> {
+        if !self.get().minters.contains(&msg::source()) {
+            panic!("Not allowed to mint")
+        };
+        self.ensure_not_paused();
+        if to == ActorId::zero() {
+            return checked(|| Err(Error::ZeroAddress));
+        }
+
+        let escrow = exec::program_id();
+        checked(|| {
+            funcs::mint(
+                Storage::balances(),
+                Storage::total_supply(),
+                self.get_mut(),
+                escrow,
+                ids.clone(),
+                amounts.clone(),
+                token_metadata,
+            )
+        })?;
+
+        let accepted = vmt_service::utils::receiver_accepts(to, method, args).await;
+
+        // Nobody but this program spends the escrow, so it still holds the tokens.
+        if !accepted {
+            funcs::revert_mint(
+                Storage::balances(),
+                Storage::total_supply(),
+                self.get_mut(),
+                escrow,
+                ids,
+                amounts,
+            )
+            .expect("Escrow covers the mint");
+
+            return Ok(None);
+        }
+
+        vmt_service::funcs::transfer_from(
+            Storage::balances(),
+            Storage::allowances(),
+            Storage::scoped_allowances(),
+            escrow,
+            escrow,
+            to,
+            ids.clone(),
+            amounts.clone(),
+        )
+        .expect("Escrow covers the mint");
+        funcs::transfer_ownership(self.get_mut(), to, &ids, &amounts);
+
+        Ok(Some(Event::Minted { to, ids, amounts }))
+    }
+}
+
+/// Rejects the inherited methods while the service is paused, and keeps track
+/// of the owners of transferred non-fungible tokens.
+struct ExtendedHooks;
+
+impl Hooks for ExtendedHooks {
+    fn before_call(&self) {
+        ExtendedService::new().ensure_not_paused();
+    }
+
+    fn after_transfer(&self, _from: ActorId, to: ActorId, ids: &[TokenId], amounts: &[U256]) {
+        funcs::transfer_ownership(ExtendedService::new().get_mut(), to, ids, amounts);
+    }
 }
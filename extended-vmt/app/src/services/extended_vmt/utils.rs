@@ -15,6 +15,8 @@ pub enum Error {
     AmountGreaterThanOneForNft,
     WrongId,
     NotEnoughBalance,
+    MaxSupplyExceeded,
+    InvalidMaxSupply,
 }
 
 #[derive(Debug, Decode, Encode, TypeInfo, Default, Clone, PartialEq, Eq)]
@@ -26,3 +28,24 @@ pub struct TokenMetadata {
     pub media: Option<String>,
     pub reference: Option<String>,
 }
+
+#[derive(Debug, Decode, Encode, TypeInfo, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub enum TokenKind {
+    Fungible,
+    /// Unique token, its max supply being one.
+    NonFungible,
+    /// Fungible token that has metadata, like a ticket or an edition.
+    SemiFungible,
+}
+
+/// Token registered with `create_token`, minting being bounded by it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenInfo {
+    pub kind: TokenKind,
+    pub max_supply: Option<U256>,
+    /// Amount minted so far, burns not lowering it.
+    pub minted: U256,
+    pub creator: ActorId,
+}
@@ -1,2 +1 @@
 pub mod extended_vmt;
-pub mod utils;
@@ -0,0 +1,458 @@
+use extended_vmt_client::{
+    traits::{ExtendedVmtFactory, Vmt},
+    ExtendedVmtFactory as Factory, ExtendedVmtUtilsError as ExtendedError, TokenMetadata,
+    Vmt as VmtClient,
+};
+use gtest::{Program, WasmProgram};
+use sails_rs::calls::*;
+use sails_rs::gtest::{calls::*, System};
+use sails_rs::{ActorId, Encode};
+
+pub const ADMIN_ID: u64 = 10;
+pub const USER_ID: [u64; 2] = [11, 12];
+
+/// A program replying with the same payload to every message.
+#[derive(Clone, Debug)]
+struct MockReceiver(Vec<u8>);
+
+impl WasmProgram for MockReceiver {
+    fn init(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        Ok(None)
+    }
+
+    fn handle(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        Ok(Some(self.0.clone()))
+    }
+
+    fn clone_boxed(&self) -> Box<dyn WasmProgram> {
+        Box::new(self.clone())
+    }
+
+    fn state(&mut self) -> Result<Vec<u8>, &'static str> {
+        Ok(vec![])
+    }
+}
+
+/// Deploys a program replying `reply` to every message.
+fn mock_receiver(system: &System, reply: impl Encode) -> ActorId {
+    let receiver = Program::mock(system, MockReceiver(reply.encode()));
+    receiver.send_bytes(ADMIN_ID, []);
+    system.run_next_block();
+    receiver.id()
+}
+
+#[tokio::test]
+async fn test_safe_transfer() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vmt.opt.wasm");
+
+    let extended_vmt_factory = Factory::new(program_space.clone());
+    let extended_vmt_id = extended_vmt_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+    // a program without `VmtReceiver` service to receive tokens
+    let receiver_id = extended_vmt_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "456")
+        .await
+        .unwrap();
+
+    let mut client = VmtClient::new(program_space);
+    let id = 1.into();
+    client
+        .mint(ADMIN_ID.into(), id, 1_000.into(), None)
+        .send_recv(extended_vmt_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // users have no hook to call and always receive the tokens
+    let res = client
+        .safe_transfer_from(ADMIN_ID.into(), USER_ID[0].into(), id, 100.into(), vec![])
+        .send_recv(extended_vmt_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(res);
+    let balance = client
+        .balance_of(USER_ID[0].into(), id)
+        .recv(extended_vmt_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 100.into());
+
+    // transfer is rejected by receiver and rolled back
+    let res = client
+        .safe_batch_transfer_from(
+            ADMIN_ID.into(),
+            receiver_id,
+            vec![id],
+            vec![100.into()],
+            vec![],
+        )
+        .send_recv(extended_vmt_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!res);
+    let balance = client
+        .balance_of(ADMIN_ID.into(), id)
+        .recv(extended_vmt_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 900.into());
+    let balance = client
+        .balance_of(receiver_id, id)
+        .recv(extended_vmt_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 0.into());
+
+    // mint is rejected by receiver and undone
+    let res = client
+        .safe_mint(receiver_id, 2.into(), 10.into(), None, vec![])
+        .send_recv(extended_vmt_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Ok(false));
+    let balance = client
+        .balance_of(receiver_id, 2.into())
+        .recv(extended_vmt_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 0.into());
+    let total_supply = client.total_supply().recv(extended_vmt_id).await.unwrap();
+    assert_eq!(total_supply, vec![(id, 1_000.into())]);
+}
+
+#[tokio::test]
+async fn test_safe_transfer_accepted() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+    let receiver_id = mock_receiver(&system, ("VmtReceiver", "OnVmtReceived", true));
+    let batch_receiver_id = mock_receiver(&system, ("VmtReceiver", "OnVmtBatchReceived", true));
+    let refusing_id = mock_receiver(&system, ("VmtReceiver", "OnVmtReceived", false));
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vmt.opt.wasm");
+
+    let extended_vmt_factory = Factory::new(program_space.clone());
+    let extended_vmt_id = extended_vmt_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VmtClient::new(program_space);
+    let id = 1.into();
+    client
+        .mint(ADMIN_ID.into(), id, 1_000.into(), None)
+        .send_recv(extended_vmt_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // transfers accepted by receivers
+    let res = client
+        .safe_transfer_from(ADMIN_ID.into(), receiver_id, id, 100.into(), vec![])
+        .send_recv(extended_vmt_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(res);
+    let res = client
+        .safe_batch_transfer_from(
+            ADMIN_ID.into(),
+            batch_receiver_id,
+            vec![id],
+            vec![200.into()],
+            vec![],
+        )
+        .send_recv(extended_vmt_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(res);
+    for (account, expected) in [
+        (ADMIN_ID.into(), 700),
+        (receiver_id, 100),
+        (batch_receiver_id, 200),
+        (extended_vmt_id, 0),
+    ] {
+        let balance = client
+            .balance_of(account, id)
+            .recv(extended_vmt_id)
+            .await
+            .unwrap();
+        assert_eq!(balance, expected.into());
+    }
+
+    // refused transfer
+    let res = client
+        .safe_transfer_from(ADMIN_ID.into(), refusing_id, id, 100.into(), vec![])
+        .send_recv(extended_vmt_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!res);
+    let balance = client
+        .balance_of(ADMIN_ID.into(), id)
+        .recv(extended_vmt_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 700.into());
+
+    // nft minted to an accepting receiver and to a user
+    let metadata = TokenMetadata {
+        title: Some("title".to_string()),
+        description: None,
+        media: None,
+        reference: None,
+    };
+    for (to, nft_id) in [(receiver_id, 2.into()), (USER_ID[0].into(), 3.into())] {
+        let res = client
+            .safe_mint(to, nft_id, 1.into(), Some(metadata.clone()), vec![])
+            .send_recv(extended_vmt_id)
+            .await
+            .unwrap();
+        assert_eq!(res, Ok(true));
+        let balance = client
+            .balance_of(to, nft_id)
+            .recv(extended_vmt_id)
+            .await
+            .unwrap();
+        assert_eq!(balance, 1.into());
+        let owner = client.owner_of(nft_id).recv(extended_vmt_id).await.unwrap();
+        assert_eq!(owner, Some(to));
+    }
+}
+
+#[tokio::test]
+async fn test_panicking_errors() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vmt.opt.wasm");
+
+    let extended_vmt_factory = Factory::new(program_space.clone());
+    let extended_vmt_id = extended_vmt_factory
+        .new_with_panicking_errors("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VmtClient::new(program_space);
+    let id = 1.into();
+    client
+        .mint(ADMIN_ID.into(), id, 100.into(), None)
+        .send_recv(extended_vmt_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // errors end in an error reply instead of being returned
+    let res = client
+        .transfer_from(ADMIN_ID.into(), USER_ID[0].into(), id, 200.into())
+        .send_recv(extended_vmt_id)
+        .await;
+    assert!(res.is_err());
+    let res = client
+        .burn(ADMIN_ID.into(), id, 200.into())
+        .send_recv(extended_vmt_id)
+        .await;
+    assert!(res.is_err());
+    let res = client
+        .transfer_from(ADMIN_ID.into(), USER_ID[0].into(), id, 50.into())
+        .send_recv(extended_vmt_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Ok(()));
+}
+
+#[tokio::test]
+async fn test_token_owners() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vmt.opt.wasm");
+
+    let extended_vmt_factory = Factory::new(program_space.clone());
+    let extended_vmt_id = extended_vmt_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VmtClient::new(program_space);
+    let metadata = TokenMetadata {
+        title: Some("title".to_string()),
+        description: None,
+        media: None,
+        reference: None,
+    };
+    // mint a fungible token and two nfts
+    client
+        .mint_batch(
+            ADMIN_ID.into(),
+            vec![1.into(), 2.into(), 3.into()],
+            vec![100.into(), 1.into(), 1.into()],
+            vec![None, Some(metadata.clone()), Some(metadata.clone())],
+        )
+        .send_recv(extended_vmt_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let token_metadata = client
+        .token_metadata(2.into())
+        .recv(extended_vmt_id)
+        .await
+        .unwrap();
+    assert_eq!(token_metadata, Some(metadata));
+    let token_ids = client.token_ids(0, 10).recv(extended_vmt_id).await.unwrap();
+    assert_eq!(token_ids, vec![1.into(), 2.into(), 3.into()]);
+
+    // transfer an nft
+    client
+        .transfer_from(ADMIN_ID.into(), USER_ID[0].into(), 3.into(), 1.into())
+        .send_recv(extended_vmt_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let owner = client
+        .owner_of(3.into())
+        .recv(extended_vmt_id)
+        .await
+        .unwrap();
+    assert_eq!(owner, Some(USER_ID[0].into()));
+    let owner = client
+        .owner_of(1.into())
+        .recv(extended_vmt_id)
+        .await
+        .unwrap();
+    assert_eq!(owner, None);
+    let tokens = client
+        .tokens_for_owner(ADMIN_ID.into(), 0, 10)
+        .recv(extended_vmt_id)
+        .await
+        .unwrap();
+    assert_eq!(tokens, vec![2.into()]);
+    let tokens = client
+        .tokens_for_owner(USER_ID[0].into(), 0, 10)
+        .recv(extended_vmt_id)
+        .await
+        .unwrap();
+    assert_eq!(tokens, vec![3.into()]);
+
+    // an nft can't be burned once transferred
+    let res = client
+        .burn(ADMIN_ID.into(), 3.into(), 1.into())
+        .send_recv(extended_vmt_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Err(ExtendedError::NotEnoughBalance));
+}
+
+#[tokio::test]
+async fn test_pause() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vmt.opt.wasm");
+
+    let extended_vmt_factory = Factory::new(program_space.clone());
+    let extended_vmt_id = extended_vmt_factory
+        .new("name".to_string(), "symbol".to_string(), 10)
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VmtClient::new(program_space);
+    let id = 1.into();
+    // mint
+    client
+        .mint(ADMIN_ID.into(), id, 1_000.into(), None)
+        .send_recv(extended_vmt_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    client
+        .set_approval_for_all(USER_ID[0].into(), true)
+        .send_recv(extended_vmt_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // pause
+    let res = client.pause().send_recv(extended_vmt_id).await.unwrap();
+    assert!(res);
+    let paused = client.paused().recv(extended_vmt_id).await.unwrap();
+    assert!(paused);
+
+    // mint, transfer and approve are rejected
+    let res = client
+        .mint(ADMIN_ID.into(), id, 1_000.into(), None)
+        .send_recv(extended_vmt_id)
+        .await;
+    assert!(res.is_err());
+    let res = client
+        .transfer_from(ADMIN_ID.into(), USER_ID[0].into(), id, 100.into())
+        .send_recv(extended_vmt_id)
+        .await;
+    assert!(res.is_err());
+    let res = client
+        .approve(USER_ID[0].into())
+        .send_recv(extended_vmt_id)
+        .await;
+    assert!(res.is_err());
+
+    // approvals can still be revoked
+    let res = client
+        .set_approval_for_all(USER_ID[0].into(), false)
+        .send_recv(extended_vmt_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(res);
+
+    // unpause
+    let res = client.unpause().send_recv(extended_vmt_id).await.unwrap();
+    assert!(res);
+    client
+        .transfer_from(ADMIN_ID.into(), USER_ID[0].into(), id, 100.into())
+        .send_recv(extended_vmt_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let balance = client
+        .balance_of(USER_ID[0].into(), id)
+        .recv(extended_vmt_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, 100.into());
+}
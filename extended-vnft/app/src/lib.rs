@@ -13,6 +13,13 @@ impl ExtendedVnftProgram {
         Self(())
     }
 
+    /// Same as `new`, but the inherited methods panic on errors instead of
+    /// returning them, for callers relying on error replies.
+    pub fn new_with_panicking_errors(name: String, symbol: String) -> Self {
+        ExtendedService::init(name, symbol).with_panicking_errors();
+        Self(())
+    }
+
     pub fn vnft(&self) -> ExtendedService {
         ExtendedService::new()
     }
@@ -5,9 +5,8 @@ use sails_rs::{
     prelude::*,
 };
 mod funcs;
-use crate::services;
-use vnft_service::utils::TokenId;
-use vnft_service::{Service as VnftService, Storage};
+use vnft_service::utils::{self, TokenId};
+use vnft_service::{checked, Hooks, Service as VnftService, Storage};
 
 #[derive(Default)]
 pub struct ExtendedStorage {
@@ -15,6 +14,7 @@ pub struct ExtendedStorage {
     minters: HashSet<ActorId>,
     burners: HashSet<ActorId>,
     admins: HashSet<ActorId>,
+    paused: bool,
     token_metadata_by_id: HashMap<TokenId, TokenMetadata>,
 }
 
@@ -43,6 +43,8 @@ pub enum Event {
         from: ActorId,
         token_id: TokenId,
     },
+    Paused,
+    Unpaused,
 }
 #[derive(Clone)]
 pub struct ExtendedService {
@@ -67,7 +69,14 @@ impl ExtendedService {
             });
         };
         ExtendedService {
-            vnft: <VnftService>::init(name, symbol),
+            vnft: <VnftService>::init(name, symbol).with_hooks(&ExtendedHooks),
+        }
+    }
+
+    /// Makes the inherited methods panic on errors instead of returning them.
+    pub fn with_panicking_errors(self) -> Self {
+        ExtendedService {
+            vnft: self.vnft.with_panicking_errors(),
         }
     }
 
@@ -96,11 +105,12 @@ impl From<ExtendedService> for VnftService {
 #[service(extends = VnftService, events = Event)]
 impl ExtendedService {
     #[export]
-    pub fn mint(&mut self, to: ActorId, token_metadata: TokenMetadata) {
+    pub fn mint(&mut self, to: ActorId, token_metadata: TokenMetadata) -> utils::Result<()> {
         if !self.get().minters.contains(&msg::source()) {
             panic!("Not allowed to mint")
         };
-        services::utils::panicking(|| {
+        self.ensure_not_paused();
+        checked(|| {
             funcs::mint(
                 Storage::owner_by_id(),
                 Storage::tokens_for_owner(),
@@ -109,17 +119,20 @@ impl ExtendedService {
                 to,
                 token_metadata.clone(),
             )
-        });
+        })?;
         self.emit_event(Event::Minted { to, token_metadata })
             .expect("Notification Error");
+
+        Ok(())
     }
 
     #[export]
-    pub fn burn(&mut self, from: ActorId, token_id: TokenId) {
+    pub fn burn(&mut self, from: ActorId, token_id: TokenId) -> utils::Result<()> {
         if !self.get().burners.contains(&msg::source()) {
             panic!("Not allowed to burn")
         };
-        services::utils::panicking(|| {
+        self.ensure_not_paused();
+        checked(|| {
             funcs::burn(
                 Storage::owner_by_id(),
                 Storage::tokens_for_owner(),
@@ -127,9 +140,41 @@ impl ExtendedService {
                 &mut self.get_mut().token_metadata_by_id,
                 token_id,
             )
-        });
+        })?;
         self.emit_event(Event::Burned { from, token_id })
             .expect("Notification Error");
+
+        Ok(())
+    }
+
+    #[export]
+    pub fn pause(&mut self) -> bool {
+        self.ensure_is_admin();
+
+        let mutated = !self.get().paused;
+        if mutated {
+            self.get_mut().paused = true;
+            self.emit_event(Event::Paused).expect("Notification Error");
+        }
+        mutated
+    }
+
+    #[export]
+    pub fn unpause(&mut self) -> bool {
+        self.ensure_is_admin();
+
+        let mutated = self.get().paused;
+        if mutated {
+            self.get_mut().paused = false;
+            self.emit_event(Event::Unpaused)
+                .expect("Notification Error");
+        }
+        mutated
+    }
+
+    #[export]
+    pub fn paused(&self) -> bool {
+        self.get().paused
     }
 
     #[export]
@@ -213,4 +258,19 @@ impl ExtendedService {
             panic!("Not admin")
         };
     }
+
+    fn ensure_not_paused(&self) {
+        if self.get().paused {
+            panic!("Paused")
+        };
+    }
+}
+
+/// Rejects the inherited methods while the service is paused.
+struct ExtendedHooks;
+
+impl Hooks for ExtendedHooks {
+    fn before_call(&self) {
+        ExtendedService::new().ensure_not_paused();
+    }
 }
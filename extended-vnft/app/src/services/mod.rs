@@ -1,2 +1 @@
 pub mod extended_vnft;
-pub mod utils;
@@ -1,13 +0,0 @@
-use core::fmt::Debug;
-use gstd::{ext, format};
-
-pub fn panicking<T, E: Debug, F: FnOnce() -> Result<T, E>>(f: F) -> T {
-    match f() {
-        Ok(v) => v,
-        Err(e) => panic(e),
-    }
-}
-
-pub fn panic(err: impl Debug) -> ! {
-    ext::panic(format!("{err:?}"))
-}
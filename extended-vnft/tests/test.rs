@@ -1,6 +1,6 @@
 use extended_vnft_client::{
     traits::{ExtendedVnftFactory, Vnft},
-    ExtendedVnftFactory as Factory, TokenMetadata, Vnft as VftClient,
+    Error, ExtendedVnftFactory as Factory, TokenMetadata, Vnft as VftClient,
 };
 use sails_rs::calls::*;
 use sails_rs::gtest::{calls::*, System};
@@ -43,6 +43,7 @@ async fn test_basic_function() {
         .mint(ADMIN_ID.into(), metadata)
         .send_recv(extended_vnft_id)
         .await
+        .unwrap()
         .unwrap();
     // check balance
     let balance = client
@@ -67,6 +68,7 @@ async fn test_basic_function() {
         .transfer(USER_ID[0].into(), 0.into())
         .send_recv(extended_vnft_id)
         .await
+        .unwrap()
         .unwrap();
     // check owner
     let actor_id = client
@@ -82,6 +84,7 @@ async fn test_basic_function() {
         .with_args(|args| args.with_actor_id(USER_ID[0].into()))
         .send_recv(extended_vnft_id)
         .await
+        .unwrap()
         .unwrap();
 
     // transfer from
@@ -90,6 +93,7 @@ async fn test_basic_function() {
         .with_args(|args| args.with_actor_id(USER_ID[1].into()))
         .send_recv(extended_vnft_id)
         .await
+        .unwrap()
         .unwrap();
     // check owner
     let actor_id = client
@@ -104,6 +108,7 @@ async fn test_basic_function() {
         .burn(ADMIN_ID.into(), 0.into())
         .send_recv(extended_vnft_id)
         .await
+        .unwrap()
         .unwrap();
     // check balance
     let balance = client
@@ -119,6 +124,67 @@ async fn test_basic_function() {
         .await
         .unwrap();
     assert_eq!(actor_id, 0.into());
+    // burn again
+    let res = client
+        .burn(ADMIN_ID.into(), 0.into())
+        .send_recv(extended_vnft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Err(Error::TokenDoesNotExist));
+}
+
+#[tokio::test]
+async fn test_panicking_errors() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vnft.opt.wasm");
+
+    let extended_vnft_factory = Factory::new(program_space.clone());
+    let extended_vnft_id = extended_vnft_factory
+        .new_with_panicking_errors(
+            "collection_name".to_string(),
+            "collection_symbol".to_string(),
+        )
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space);
+    let metadata = TokenMetadata {
+        name: "token_name".to_string(),
+        description: "token_description".to_string(),
+        media: "token_media".to_string(),
+        reference: "token_reference".to_string(),
+    };
+    client
+        .mint(ADMIN_ID.into(), metadata)
+        .send_recv(extended_vnft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // errors end in an error reply instead of being returned
+    let res = client
+        .transfer(USER_ID[0].into(), 1.into())
+        .send_recv(extended_vnft_id)
+        .await;
+    assert!(res.is_err());
+    let res = client
+        .burn(ADMIN_ID.into(), 1.into())
+        .send_recv(extended_vnft_id)
+        .await;
+    assert!(res.is_err());
+    let res = client
+        .transfer(USER_ID[0].into(), 0.into())
+        .send_recv(extended_vnft_id)
+        .await
+        .unwrap();
+    assert_eq!(res, Ok(()));
 }
 
 #[tokio::test]
@@ -178,6 +244,7 @@ async fn test_grant_role() {
         .with_args(|args| args.with_actor_id(USER_ID[0].into()))
         .send_recv(extended_vft_id)
         .await
+        .unwrap()
         .unwrap();
 
     let balance = client
@@ -208,6 +275,7 @@ async fn test_grant_role() {
         .with_args(|args| args.with_actor_id(USER_ID[0].into()))
         .send_recv(extended_vft_id)
         .await
+        .unwrap()
         .unwrap();
 
     let balance = client
@@ -249,3 +317,74 @@ async fn test_grant_role() {
     let burners = client.burners().recv(extended_vft_id).await.unwrap();
     assert_eq!(burners, vec![ADMIN_ID.into()]);
 }
+
+#[tokio::test]
+async fn test_pause() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000_000_000_000_000);
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/extended_vnft.opt.wasm");
+
+    let extended_vnft_factory = Factory::new(program_space.clone());
+    let extended_vnft_id = extended_vnft_factory
+        .new(
+            "collection_name".to_string(),
+            "collection_symbol".to_string(),
+        )
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space);
+    // mint
+    let metadata = TokenMetadata {
+        name: "token_name".to_string(),
+        description: "token_description".to_string(),
+        media: "token_media".to_string(),
+        reference: "token_reference".to_string(),
+    };
+    client
+        .mint(ADMIN_ID.into(), metadata.clone())
+        .send_recv(extended_vnft_id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // pause
+    let res = client.pause().send_recv(extended_vnft_id).await.unwrap();
+    assert!(res);
+    let paused = client.paused().recv(extended_vnft_id).await.unwrap();
+    assert!(paused);
+
+    // mint and transfer are rejected
+    let res = client
+        .mint(ADMIN_ID.into(), metadata)
+        .send_recv(extended_vnft_id)
+        .await;
+    assert!(res.is_err());
+    let res = client
+        .transfer(USER_ID[0].into(), 0.into())
+        .send_recv(extended_vnft_id)
+        .await;
+    assert!(res.is_err());
+
+    // unpause
+    let res = client.unpause().send_recv(extended_vnft_id).await.unwrap();
+    assert!(res);
+    client
+        .transfer(USER_ID[0].into(), 0.into())
+        .send_recv(extended_vnft_id)
+        .await
+        .unwrap()
+        .unwrap();
+    let actor_id = client
+        .owner_of(0.into())
+        .recv(extended_vnft_id)
+        .await
+        .unwrap();
+    assert_eq!(actor_id, USER_ID[0].into());
+}
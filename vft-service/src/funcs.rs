@@ -29,10 +29,328 @@ pub fn approve(
     prev.map(|v| v != value).unwrap_or(true)
 }
 
+pub fn increase_allowance(
+    allowances: &mut AllowancesMap,
+    owner: ActorId,
+    spender: ActorId,
+    value: U256,
+) -> Result<bool> {
+    let new_allowance = allowance(allowances, owner, spender)
+        .checked_add(value)
+        .ok_or(Error::NumericOverflow)?;
+
+    Ok(approve(allowances, owner, spender, new_allowance))
+}
+
+pub fn decrease_allowance(
+    allowances: &mut AllowancesMap,
+    owner: ActorId,
+    spender: ActorId,
+    value: U256,
+) -> Result<bool> {
+    let new_allowance = allowance(allowances, owner, spender)
+        .checked_sub(value)
+        .ok_or(Error::InsufficientAllowance)?;
+
+    Ok(approve(allowances, owner, spender, new_allowance))
+}
+
+// Allowances of `owner`, which are adjacent in the map.
+fn allowances_range(
+    allowances: &AllowancesMap,
+    owner: ActorId,
+) -> impl Iterator<Item = (ActorId, U256)> + '_ {
+    allowances
+        .range((owner, ActorId::zero())..=(owner, ActorId::from([u8::MAX; 32])))
+        .map(|((_, spender), value)| (*spender, *value))
+}
+
+pub fn spenders_count(allowances: &AllowancesMap, owner: ActorId) -> u32 {
+    allowances_range(allowances, owner).count() as u32
+}
+
+/// Returns a page of spenders of `owner` with their allowances, ordered by id.
+pub fn allowances_of(
+    allowances: &AllowancesMap,
+    owner: ActorId,
+    offset: u32,
+    limit: u32,
+) -> Vec<(ActorId, U256)> {
+    allowances_range(allowances, owner)
+        .skip(offset as usize)
+        .take(limit as usize)
+        .collect()
+}
+
+/// Removes every allowance over the tokens of `owner`, returning the spenders
+/// that had one.
+pub fn revoke_all_allowances(allowances: &mut AllowancesMap, owner: ActorId) -> Vec<ActorId> {
+    let spenders: Vec<_> = allowances_range(allowances, owner)
+        .map(|(spender, _)| spender)
+        .collect();
+
+    for spender in &spenders {
+        allowances.remove(&(owner, *spender));
+    }
+
+    spenders
+}
+
+pub fn nonce(nonces: &NoncesMap, owner: ActorId) -> U256 {
+    nonces.get(&owner).cloned().unwrap_or_default()
+}
+
+/// Returns the bytes the owner signs to authorize `permit` on `program`.
+///
+/// The SCALE-encoded payload is wrapped into `<Bytes>...</Bytes>`, the same
+/// way wallet extensions do for raw signing requests.
+pub fn permit_message(program: ActorId, permit: &Permit) -> Vec<u8> {
+    [
+        b"<Bytes>".as_slice(),
+        &(PERMIT_DOMAIN, program, permit).encode(),
+        b"</Bytes>".as_slice(),
+    ]
+    .concat()
+}
+
+pub fn permit(
+    allowances: &mut AllowancesMap,
+    nonces: &mut NoncesMap,
+    program: ActorId,
+    now: u64,
+    permit: &Permit,
+    signature: &[u8; 64],
+) -> Result<bool> {
+    if now > permit.deadline {
+        return Err(Error::PermitExpired);
+    }
+
+    let current = nonce(nonces, permit.owner);
+
+    if permit.nonce != current {
+        return Err(Error::InvalidNonce);
+    }
+
+    let message = permit_message(program, permit);
+
+    if !verify_signature(permit.owner, &message, signature) {
+        return Err(Error::InvalidSignature);
+    }
+
+    let next = current
+        .checked_add(U256::one())
+        .ok_or(Error::NumericOverflow)?;
+    nonces.insert(permit.owner, next);
+
+    Ok(approve(
+        allowances,
+        permit.owner,
+        permit.spender,
+        permit.value,
+    ))
+}
+
+// Accounts are either sr25519 or ed25519 public keys, so both schemes are tried.
+fn verify_signature(signer: ActorId, message: &[u8], signature: &[u8; 64]) -> bool {
+    let public = signer.into_bytes();
+
+    let sr25519 = schnorrkel::PublicKey::from_bytes(&public).and_then(|key| {
+        let signature = schnorrkel::Signature::from_bytes(signature)?;
+        key.verify_simple(b"substrate", message, &signature)
+    });
+
+    if sr25519.is_ok() {
+        return true;
+    }
+
+    ed25519_dalek::VerifyingKey::from_bytes(&public)
+        .and_then(|key| {
+            key.verify_strict(message, &ed25519_dalek::Signature::from_bytes(signature))
+        })
+        .is_ok()
+}
+
+/// Returns the spendable balance of `owner`, its balance on hold is kept
+/// apart in [`HeldMap`].
 pub fn balance_of(balances: &BalancesMap, owner: ActorId) -> U256 {
     balances.get(&owner).cloned().unwrap_or_default()
 }
 
+pub fn balance_on_hold(held: &HeldMap, owner: ActorId) -> U256 {
+    held.get(&owner).cloned().unwrap_or_default()
+}
+
+/// Returns the whole balance of `owner`, including its balance on hold.
+pub fn total_balance_of(balances: &BalancesMap, held: &HeldMap, owner: ActorId) -> U256 {
+    balance_of(balances, owner).saturating_add(balance_on_hold(held, owner))
+}
+
+pub fn holders_count(balances: &BalancesMap, held: &HeldMap) -> u32 {
+    let only_held = held
+        .keys()
+        .filter(|holder| !balances.contains_key(holder))
+        .count();
+
+    (balances.len() + only_held) as u32
+}
+
+/// Returns a page of holders with their total balances, ordered by id.
+/// Zero balances are never stored, so every holder listed has some.
+pub fn holders(
+    balances: &BalancesMap,
+    held: &HeldMap,
+    offset: u32,
+    limit: u32,
+) -> Vec<(ActorId, U256)> {
+    let mut balances = balances.iter().peekable();
+    let mut held = held.iter().peekable();
+
+    // Merges both maps, which are ordered by id.
+    let merged = core::iter::from_fn(|| {
+        let next_balance = balances.peek().map(|(holder, _)| **holder);
+        let next_held = held.peek().map(|(holder, _)| **holder);
+
+        let holder = match (next_balance, next_held) {
+            (Some(a), Some(b)) => a.min(b),
+            (Some(a), None) => a,
+            (None, Some(b)) => b,
+            (None, None) => return None,
+        };
+
+        let balance = balances
+            .next_if(|(next, _)| **next == holder)
+            .map(|(_, value)| *value)
+            .unwrap_or_default();
+        let on_hold = held
+            .next_if(|(next, _)| **next == holder)
+            .map(|(_, value)| *value)
+            .unwrap_or_default();
+
+        Some((holder, balance.saturating_add(on_hold)))
+    });
+
+    merged.skip(offset as usize).take(limit as usize).collect()
+}
+
+/// Puts `hold.value` of the holder's balance on hold under `operation_id`.
+/// The value leaves the spendable balance but stays in the total one.
+pub fn hold(
+    balances: &mut BalancesMap,
+    held: &mut HeldMap,
+    holds: &mut HoldsMap,
+    operation_id: U256,
+    hold: Hold,
+    now: u64,
+) -> Result<bool> {
+    if holds.contains_key(&operation_id) {
+        return Err(Error::HoldExists);
+    }
+
+    if hold.is_expired(now) {
+        return Err(Error::HoldExpired);
+    }
+
+    if hold.value.is_zero() {
+        return Ok(false);
+    }
+
+    let new_balance = balance_of(balances, hold.holder)
+        .checked_sub(hold.value)
+        .ok_or(Error::InsufficientBalance)?;
+
+    let new_held = balance_on_hold(held, hold.holder)
+        .checked_add(hold.value)
+        .ok_or(Error::NumericOverflow)?;
+
+    if !new_balance.is_zero() {
+        balances.insert(hold.holder, new_balance);
+    } else {
+        balances.remove(&hold.holder);
+    }
+
+    held.insert(hold.holder, new_held);
+    holds.insert(operation_id, hold);
+
+    Ok(true)
+}
+
+/// Returns the held value to the holder. Allowed to the notary, or to
+/// anyone once the hold expired.
+pub fn release_hold(
+    balances: &mut BalancesMap,
+    held: &mut HeldMap,
+    holds: &mut HoldsMap,
+    operation_id: U256,
+    caller: ActorId,
+    now: u64,
+) -> Result<Hold> {
+    let hold = holds.get(&operation_id).ok_or(Error::NonexistentHold)?;
+
+    if caller != hold.notary && !hold.is_expired(now) {
+        return Err(Error::NotNotary);
+    }
+
+    settle_hold(balances, held, holds, operation_id, None)
+}
+
+/// Moves the held value to `to`. Allowed only to the notary, until the
+/// hold expires.
+pub fn execute_hold(
+    balances: &mut BalancesMap,
+    held: &mut HeldMap,
+    holds: &mut HoldsMap,
+    operation_id: U256,
+    caller: ActorId,
+    to: ActorId,
+    now: u64,
+) -> Result<Hold> {
+    let hold = holds.get(&operation_id).ok_or(Error::NonexistentHold)?;
+
+    if caller != hold.notary {
+        return Err(Error::NotNotary);
+    }
+
+    if hold.is_expired(now) {
+        return Err(Error::HoldExpired);
+    }
+
+    settle_hold(balances, held, holds, operation_id, Some(to))
+}
+
+// Removes the hold, crediting its value to `to` or back to the holder.
+fn settle_hold(
+    balances: &mut BalancesMap,
+    held: &mut HeldMap,
+    holds: &mut HoldsMap,
+    operation_id: U256,
+    to: Option<ActorId>,
+) -> Result<Hold> {
+    let hold = holds
+        .get(&operation_id)
+        .cloned()
+        .ok_or(Error::NonexistentHold)?;
+    let to = to.unwrap_or(hold.holder);
+
+    let new_held = balance_on_hold(held, hold.holder)
+        .checked_sub(hold.value)
+        .ok_or(Error::Underflow)?;
+
+    let new_to = balance_of(balances, to)
+        .checked_add(hold.value)
+        .ok_or(Error::NumericOverflow)?;
+
+    if !new_held.is_zero() {
+        held.insert(hold.holder, new_held);
+    } else {
+        held.remove(&hold.holder);
+    }
+
+    balances.insert(to, new_to);
+    holds.remove(&operation_id);
+
+    Ok(hold)
+}
+
 pub fn transfer(
     balances: &mut BalancesMap,
     from: ActorId,
@@ -62,6 +380,32 @@ pub fn transfer(
     Ok(true)
 }
 
+/// Transfers to every recipient of the batch, failing without changes if
+/// their sum exceeds the balance of `from`. Transfers to `from` are skipped.
+pub fn transfer_batch(
+    balances: &mut BalancesMap,
+    from: ActorId,
+    transfers: &[(ActorId, U256)],
+) -> Result<bool> {
+    let total = transfers
+        .iter()
+        .filter(|(to, _)| *to != from)
+        .try_fold(U256::zero(), |total, (_, value)| total.checked_add(*value))
+        .ok_or(Error::NumericOverflow)?;
+
+    if total > balance_of(balances, from) {
+        return Err(Error::InsufficientBalance);
+    }
+
+    let mut mutated = false;
+
+    for (to, value) in transfers {
+        mutated |= transfer(balances, from, *to, *value)?;
+    }
+
+    Ok(mutated)
+}
+
 pub fn transfer_from(
     allowances: &mut AllowancesMap,
     balances: &mut BalancesMap,
@@ -100,6 +444,7 @@ pub fn transfer_from(
 mod tests {
     use super::*;
     use crate::funcs;
+    use ed25519_dalek::Signer;
     use utils::*;
 
     macro_rules! assert_ok {
@@ -137,6 +482,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn allowances_of() {
+        // Initializing thread logger.
+        let _ = env_logger::try_init();
+
+        // Creating map with approves from Alice to Charlie and Bob, and from
+        // Bob to Alice.
+        let mut map = allowances_map([
+            (alice(), charlie(), U256::exp10(20)),
+            (bob(), alice(), U256::exp10(10)),
+            (alice(), bob(), U256::exp10(42)),
+        ]);
+
+        // # Test case #1.
+        // Only allowances of the owner are listed, ordered by spender.
+        {
+            assert_eq!(funcs::spenders_count(&map, alice()), 2);
+            assert_eq!(funcs::spenders_count(&map, charlie()), 0);
+            assert_eq!(
+                funcs::allowances_of(&map, alice(), 0, 10),
+                vec![(bob(), U256::exp10(42)), (charlie(), U256::exp10(20))]
+            );
+            assert_eq!(
+                funcs::allowances_of(&map, alice(), 1, 1),
+                vec![(charlie(), U256::exp10(20))]
+            );
+            assert!(funcs::allowances_of(&map, alice(), 2, 10).is_empty());
+        }
+
+        // # Test case #2.
+        // Revoking removes only allowances of the owner.
+        {
+            assert_eq!(
+                funcs::revoke_all_allowances(&mut map, alice()),
+                vec![bob(), charlie()]
+            );
+            assert!(funcs::revoke_all_allowances(&mut map, alice()).is_empty());
+            assert_eq!(funcs::spenders_count(&map, alice()), 0);
+            assert_eq!(funcs::allowance(&map, bob(), alice()), U256::exp10(10));
+        }
+    }
+
     #[test]
     fn approve() {
         // Initializing thread logger.
@@ -195,6 +582,243 @@ mod tests {
         }
     }
 
+    #[test]
+    fn increase_allowance() {
+        // Initializing thread logger.
+        let _ = env_logger::try_init();
+
+        // Creating map with one single approve from Alice to Bob.
+        let mut map = allowances_map([(alice(), bob(), U256::exp10(42))]);
+
+        // # Test case #1.
+        // Existing allowance from Alice to Bob is increased.
+        {
+            assert_ok!(
+                funcs::increase_allowance(&mut map, alice(), bob(), U256::exp10(42)),
+                true
+            );
+            assert_eq!(
+                funcs::allowance(&map, alice(), bob()),
+                U256::exp10(42).saturating_mul(2.into())
+            );
+        }
+
+        // # Test case #2.
+        // Allowance from Alice to Charlie doesn't exist and created.
+        {
+            assert_ok!(
+                funcs::increase_allowance(&mut map, alice(), charlie(), U256::exp10(24)),
+                true
+            );
+            assert_eq!(funcs::allowance(&map, alice(), charlie()), U256::exp10(24));
+        }
+
+        // # Test case #3.
+        // Allowance isn't changed on zero value.
+        {
+            assert_ok!(
+                funcs::increase_allowance(&mut map, alice(), charlie(), U256::zero()),
+                false
+            );
+            assert_eq!(funcs::allowance(&map, alice(), charlie()), U256::exp10(24));
+        }
+
+        // # Test case #4.
+        // Increase causing numeric overflow.
+        {
+            assert_err!(
+                funcs::increase_allowance(&mut map, alice(), charlie(), U256::MAX),
+                Error::NumericOverflow
+            );
+            assert_eq!(funcs::allowance(&map, alice(), charlie()), U256::exp10(24));
+        }
+
+        // # Test case #5.
+        // Increase is always noop on owner == spender.
+        {
+            assert_ok!(
+                funcs::increase_allowance(&mut map, alice(), alice(), U256::exp10(42)),
+                false
+            );
+            assert!(funcs::allowance(&map, alice(), alice()).is_zero());
+        }
+    }
+
+    #[test]
+    fn decrease_allowance() {
+        // Initializing thread logger.
+        let _ = env_logger::try_init();
+
+        // Creating map with one single approve from Alice to Bob.
+        let mut map = allowances_map([(alice(), bob(), U256::exp10(42))]);
+
+        // # Test case #1.
+        // Existing allowance from Alice to Bob is decreased.
+        {
+            assert_ok!(
+                funcs::decrease_allowance(&mut map, alice(), bob(), U256::exp10(24)),
+                true
+            );
+            assert_eq!(
+                funcs::allowance(&map, alice(), bob()),
+                U256::exp10(42) - U256::exp10(24)
+            );
+        }
+
+        // # Test case #2.
+        // Decrease below zero is rejected.
+        {
+            assert_err!(
+                funcs::decrease_allowance(&mut map, alice(), bob(), U256::exp10(42)),
+                Error::InsufficientAllowance
+            );
+            assert_eq!(
+                funcs::allowance(&map, alice(), bob()),
+                U256::exp10(42) - U256::exp10(24)
+            );
+        }
+
+        // # Test case #3.
+        // Allowance from Alice to Bob is decreased to zero and removed.
+        {
+            assert_ok!(
+                funcs::decrease_allowance(
+                    &mut map,
+                    alice(),
+                    bob(),
+                    U256::exp10(42) - U256::exp10(24)
+                ),
+                true
+            );
+            assert!(!map.contains_key(&(alice(), bob())));
+        }
+
+        // # Test case #4.
+        // Allowance from Alice to Charlie doesn't exist.
+        {
+            assert_err!(
+                funcs::decrease_allowance(&mut map, alice(), charlie(), U256::one()),
+                Error::InsufficientAllowance
+            );
+            assert_ok!(
+                funcs::decrease_allowance(&mut map, alice(), charlie(), U256::zero()),
+                false
+            );
+        }
+    }
+
+    #[test]
+    fn permit() {
+        // Initializing thread logger.
+        let _ = env_logger::try_init();
+
+        // Creating empty maps and two owners: with sr25519 and ed25519 keys.
+        let mut amap = allowances_map([]);
+        let mut nmap = NoncesMap::new();
+
+        let sr25519 = sr25519_keypair();
+        let sr25519_owner = ActorId::new(sr25519.public.to_bytes());
+        let sign_sr25519 = |message: &[u8]| sr25519.sign_simple(b"substrate", message).to_bytes();
+
+        let ed25519 = ed25519_keypair();
+        let ed25519_owner = ActorId::new(ed25519.verifying_key().to_bytes());
+        let sign_ed25519 = |message: &[u8]| ed25519.sign(message).to_bytes();
+
+        // # Test case #1.
+        // Permit signed with sr25519 key is applied and nonce is increased.
+        {
+            let permit = permit_of(sr25519_owner, U256::exp10(42), 0.into());
+            let signature = sign_sr25519(&funcs::permit_message(program(), &permit));
+
+            assert_ok!(
+                funcs::permit(&mut amap, &mut nmap, program(), NOW, &permit, &signature),
+                true
+            );
+            assert_eq!(
+                funcs::allowance(&amap, sr25519_owner, bob()),
+                U256::exp10(42)
+            );
+            assert_eq!(funcs::nonce(&nmap, sr25519_owner), U256::one());
+        }
+
+        // # Test case #2.
+        // The same permit can't be replayed.
+        {
+            let permit = permit_of(sr25519_owner, U256::exp10(42), 0.into());
+            let signature = sign_sr25519(&funcs::permit_message(program(), &permit));
+
+            assert_err!(
+                funcs::permit(&mut amap, &mut nmap, program(), NOW, &permit, &signature),
+                Error::InvalidNonce
+            );
+            assert_eq!(funcs::nonce(&nmap, sr25519_owner), U256::one());
+        }
+
+        // # Test case #3.
+        // Permit is rejected after its deadline.
+        {
+            let permit = permit_of(sr25519_owner, U256::exp10(24), 1.into());
+            let signature = sign_sr25519(&funcs::permit_message(program(), &permit));
+
+            assert_err!(
+                funcs::permit(
+                    &mut amap,
+                    &mut nmap,
+                    program(),
+                    permit.deadline + 1,
+                    &permit,
+                    &signature
+                ),
+                Error::PermitExpired
+            );
+            assert_eq!(
+                funcs::allowance(&amap, sr25519_owner, bob()),
+                U256::exp10(42)
+            );
+        }
+
+        // # Test case #4.
+        // Permit signed for another program or by another key is rejected.
+        {
+            let permit = permit_of(sr25519_owner, U256::exp10(24), 1.into());
+            let signature = sign_sr25519(&funcs::permit_message(dave(), &permit));
+
+            assert_err!(
+                funcs::permit(&mut amap, &mut nmap, program(), NOW, &permit, &signature),
+                Error::InvalidSignature
+            );
+
+            let signature = sign_ed25519(&funcs::permit_message(program(), &permit));
+
+            assert_err!(
+                funcs::permit(&mut amap, &mut nmap, program(), NOW, &permit, &signature),
+                Error::InvalidSignature
+            );
+            assert_eq!(funcs::nonce(&nmap, sr25519_owner), U256::one());
+        }
+
+        // # Test case #5.
+        // Permit signed with ed25519 key removes existing allowance.
+        {
+            assert!(funcs::approve(
+                &mut amap,
+                ed25519_owner,
+                bob(),
+                U256::exp10(42)
+            ));
+
+            let permit = permit_of(ed25519_owner, U256::zero(), 0.into());
+            let signature = sign_ed25519(&funcs::permit_message(program(), &permit));
+
+            assert_ok!(
+                funcs::permit(&mut amap, &mut nmap, program(), NOW, &permit, &signature),
+                true
+            );
+            assert!(funcs::allowance(&amap, ed25519_owner, bob()).is_zero());
+            assert_eq!(funcs::nonce(&nmap, ed25519_owner), U256::one());
+        }
+    }
+
     #[test]
     fn balance_of() {
         // Initializing thread logger.
@@ -394,6 +1018,81 @@ mod tests {
         }
     }
 
+    // Since this uses [`super::transfer`] in underlying impl, it needs only
+    // check batch specific logic.
+    #[test]
+    fn transfer_batch() {
+        // Initializing thread logger.
+        let _ = env_logger::try_init();
+
+        // Creating map with medium balance belonged to Bob and max one to Dave.
+        let mut map = balances_map([(bob(), U256::exp10(20)), (dave(), U256::MAX)]);
+
+        // # Test case #1.
+        // Bob transfers more than his balance in total, nothing changes.
+        {
+            assert_err!(
+                funcs::transfer_batch(
+                    &mut map,
+                    bob(),
+                    &[(alice(), U256::exp10(19)), (charlie(), U256::exp10(20))]
+                ),
+                Error::InsufficientBalance
+            );
+
+            assert!(funcs::balance_of(&map, alice()).is_zero());
+            assert_eq!(funcs::balance_of(&map, bob()), U256::exp10(20));
+        }
+
+        // # Test case #2.
+        // Dave's batch overflows in total.
+        {
+            assert_err!(
+                funcs::transfer_batch(
+                    &mut map,
+                    dave(),
+                    &[(alice(), U256::MAX), (charlie(), U256::one())]
+                ),
+                Error::NumericOverflow
+            );
+
+            assert!(funcs::balance_of(&map, alice()).is_zero());
+        }
+
+        // # Test case #3.
+        // Empty batch and batch to self change nothing.
+        {
+            assert_ok!(funcs::transfer_batch(&mut map, bob(), &[]), false);
+            assert_ok!(
+                funcs::transfer_batch(&mut map, bob(), &[(bob(), U256::exp10(30))]),
+                false
+            );
+
+            assert_eq!(funcs::balance_of(&map, bob()), U256::exp10(20));
+        }
+
+        // # Test case #4.
+        // Bob transfers his whole balance to several recipients.
+        {
+            assert_ok!(
+                funcs::transfer_batch(
+                    &mut map,
+                    bob(),
+                    &[
+                        (alice(), U256::exp10(19)),
+                        (charlie(), U256::exp10(19)),
+                        (alice(), U256::exp10(19) * 8),
+                    ]
+                ),
+                true
+            );
+
+            assert_eq!(funcs::balance_of(&map, alice()), U256::exp10(19) * 9);
+            assert_eq!(funcs::balance_of(&map, charlie()), U256::exp10(19));
+            assert!(funcs::balance_of(&map, bob()).is_zero());
+        }
+    }
+
     // Since this uses [`super::transfer`] in underlying impl, it needs only
     // check approval specific logic and few transfer's happy cases.
     #[test]
@@ -588,9 +1287,236 @@ mod tests {
         }
     }
 
+    #[test]
+    fn hold() {
+        // Initializing thread logger.
+        let _ = env_logger::try_init();
+
+        // Creating map with medium balance belonged to Alice.
+        let mut bmap = balances_map([(alice(), U256::exp10(42))]);
+        let mut held = HeldMap::new();
+        let mut holds = HoldsMap::new();
+        let hold_of = |value: U256| Hold {
+            holder: alice(),
+            notary: bob(),
+            value,
+            expiry: NOW,
+        };
+
+        // # Test case #1.
+        // Alice can't hold more than her balance, or hold already expired.
+        {
+            assert_err!(
+                funcs::hold(
+                    &mut bmap,
+                    &mut held,
+                    &mut holds,
+                    1.into(),
+                    hold_of(U256::exp10(43)),
+                    NOW
+                ),
+                Error::InsufficientBalance
+            );
+            assert_err!(
+                funcs::hold(
+                    &mut bmap,
+                    &mut held,
+                    &mut holds,
+                    1.into(),
+                    hold_of(U256::exp10(20)),
+                    NOW + 1
+                ),
+                Error::HoldExpired
+            );
+            assert!(holds.is_empty());
+        }
+
+        // # Test case #2.
+        // Held value is no longer spendable but stays in total balance.
+        {
+            assert_ok!(
+                funcs::hold(
+                    &mut bmap,
+                    &mut held,
+                    &mut holds,
+                    1.into(),
+                    hold_of(U256::exp10(42) - 1),
+                    NOW
+                ),
+                true
+            );
+            assert_eq!(funcs::balance_of(&bmap, alice()), U256::one());
+            assert_eq!(
+                funcs::total_balance_of(&bmap, &held, alice()),
+                U256::exp10(42)
+            );
+            assert_err!(
+                funcs::transfer(&mut bmap, alice(), charlie(), U256::exp10(20)),
+                Error::InsufficientBalance
+            );
+            assert_err!(
+                funcs::hold(
+                    &mut bmap,
+                    &mut held,
+                    &mut holds,
+                    1.into(),
+                    hold_of(U256::one()),
+                    NOW
+                ),
+                Error::HoldExists
+            );
+        }
+
+        // # Test case #3.
+        // Only notary executes, until expiry.
+        {
+            assert_err!(
+                funcs::execute_hold(
+                    &mut bmap,
+                    &mut held,
+                    &mut holds,
+                    1.into(),
+                    alice(),
+                    charlie(),
+                    NOW
+                ),
+                Error::NotNotary
+            );
+            assert_err!(
+                funcs::execute_hold(
+                    &mut bmap,
+                    &mut held,
+                    &mut holds,
+                    1.into(),
+                    bob(),
+                    charlie(),
+                    NOW + 1
+                ),
+                Error::HoldExpired
+            );
+            assert_ok!(
+                funcs::execute_hold(
+                    &mut bmap,
+                    &mut held,
+                    &mut holds,
+                    1.into(),
+                    bob(),
+                    charlie(),
+                    NOW
+                ),
+                hold_of(U256::exp10(42) - 1)
+            );
+            assert_eq!(funcs::balance_of(&bmap, charlie()), U256::exp10(42) - 1);
+            assert!(funcs::balance_on_hold(&held, alice()).is_zero());
+            assert!(holds.is_empty());
+        }
+
+        // # Test case #4.
+        // Only notary releases before expiry, anyone after it.
+        {
+            assert_ok!(
+                funcs::hold(
+                    &mut bmap,
+                    &mut held,
+                    &mut holds,
+                    2.into(),
+                    hold_of(U256::one()),
+                    NOW
+                ),
+                true
+            );
+            assert_err!(
+                funcs::release_hold(&mut bmap, &mut held, &mut holds, 2.into(), charlie(), NOW),
+                Error::NotNotary
+            );
+            assert_ok!(
+                funcs::release_hold(
+                    &mut bmap,
+                    &mut held,
+                    &mut holds,
+                    2.into(),
+                    charlie(),
+                    NOW + 1
+                ),
+                hold_of(U256::one())
+            );
+            assert_eq!(funcs::balance_of(&bmap, alice()), U256::one());
+            assert_err!(
+                funcs::release_hold(&mut bmap, &mut held, &mut holds, 2.into(), bob(), NOW),
+                Error::NonexistentHold
+            );
+        }
+    }
+
+    #[test]
+    fn holders() {
+        // Initializing thread logger.
+        let _ = env_logger::try_init();
+
+        // Creating map with balances of Alice and Charlie, and one on hold of
+        // Bob and Charlie.
+        let bmap = balances_map([(charlie(), U256::exp10(20)), (alice(), U256::exp10(42))]);
+        let held: HeldMap = [(charlie(), U256::one()), (bob(), U256::exp10(10))].into();
+
+        // # Test case #1.
+        // Holders are counted once and ordered by id.
+        {
+            assert_eq!(funcs::holders_count(&bmap, &held), 3);
+            assert_eq!(
+                funcs::holders(&bmap, &held, 0, 10),
+                vec![
+                    (alice(), U256::exp10(42)),
+                    (bob(), U256::exp10(10)),
+                    (charlie(), U256::exp10(20) + 1),
+                ]
+            );
+        }
+
+        // # Test case #2.
+        // Pages are cut by offset and limit.
+        {
+            assert_eq!(
+                funcs::holders(&bmap, &held, 1, 1),
+                vec![(bob(), U256::exp10(10))]
+            );
+            assert_eq!(
+                funcs::holders(&bmap, &held, 2, 10),
+                vec![(charlie(), U256::exp10(20) + 1)]
+            );
+            assert!(funcs::holders(&bmap, &held, 3, 10).is_empty());
+            assert!(funcs::holders(&bmap, &held, 0, 0).is_empty());
+        }
+    }
+
     mod utils {
         use super::*;
 
+        pub const NOW: u64 = 1_000;
+
+        pub fn permit_of(owner: ActorId, value: U256, nonce: U256) -> Permit {
+            Permit {
+                owner,
+                spender: bob(),
+                value,
+                deadline: NOW,
+                nonce,
+            }
+        }
+
+        pub fn sr25519_keypair() -> schnorrkel::Keypair {
+            schnorrkel::MiniSecretKey::from_bytes(&[1; 32])
+                .expect("Invalid secret key")
+                .expand_to_keypair(schnorrkel::ExpansionMode::Ed25519)
+        }
+
+        pub fn ed25519_keypair() -> ed25519_dalek::SigningKey {
+            ed25519_dalek::SigningKey::from_bytes(&[2; 32])
+        }
+
+        pub fn program() -> ActorId {
+            42u64.into()
+        }
+
         pub fn allowances_map<const N: usize>(
             content: [(ActorId, ActorId, U256); N],
         ) -> AllowancesMap {
@@ -601,7 +1527,7 @@ mod tests {
         }
 
         pub fn balances_map<const N: usize>(content: [(ActorId, U256); N]) -> BalancesMap {
-            content.into_iter().map(|(k, v)| (k, v)).collect()
+            content.into_iter().collect()
         }
 
         pub fn alice() -> ActorId {
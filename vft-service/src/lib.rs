@@ -5,7 +5,7 @@
 use core::fmt::Debug;
 use sails_rs::{
     collections::HashMap,
-    gstd::{msg, service},
+    gstd::{exec, msg, service, services::Exposure},
     prelude::*,
 };
 
@@ -13,11 +13,150 @@ pub mod funcs;
 pub mod utils;
 
 static mut STORAGE: Option<Storage> = None;
+static mut HOOKS: &dyn Hooks = &();
+static mut PANICKING_ERRORS: bool = false;
+
+/// Lets a service extending [`Service`] guard the methods it inherits and take
+/// part in the balance changes they make. Every method has a no-op default.
+///
+/// Hooks reject calls by panicking, so a caller sees the rejections of the
+/// extending service, like pausing or frozen accounts, as error replies
+/// rather than [`utils::Error`] variants, with or without
+/// [`Service::with_panicking_errors`].
+pub trait Hooks {
+    /// Called first in every method of [`Service`], queries and the methods
+    /// that skip [`Hooks::before_call`] included. May panic to reject the
+    /// message, e.g. if it carries value the program doesn't accept.
+    fn before_message(&self) {}
+
+    /// Called next in every method of [`Service`] that moves tokens or
+    /// raises an allowance. May panic to reject the call. Allowances can
+    /// always be lowered, so that holders may limit what they expose.
+    fn before_call(&self) {}
+
+    /// Called before `value` is moved from `from` to `to`, while balances
+    /// still hold their previous values. May panic to reject the transfer.
+    fn before_transfer(&self, _from: ActorId, _to: ActorId, _value: U256) {}
+
+    /// Called instead of [`Hooks::before_transfer`] before `value` kept in
+    /// escrow by `transfer_and_call` is moved from `from`, the program itself,
+    /// to `to` once the receiver replied. Must not panic, as the value would
+    /// be left in escrow. For the escrow to cover the value, the extending
+    /// service must not spend the program's own balance beyond what it put
+    /// there itself.
+    fn before_escrow_release(&self, _from: ActorId, _to: ActorId, _value: U256) {}
+
+    /// Called once `value` was moved from `from` to `to`. Events may be
+    /// emitted on `route`, the route of the service that made the transfer.
+    fn after_transfer(&self, _route: &'static [u8], _from: ActorId, _to: ActorId, _value: U256) {}
+
+    /// Returns the account to credit a fee to and the fee, for a transfer of
+    /// any kind, including batched transfers, executed holds and accepted
+    /// `transfer_and_call`s, of `value` from `from` to `to`. The fee is then
+    /// transferred to that account out of what `to` received. Must not
+    /// change state, as `transfer_and_call` asks for the fee before knowing
+    /// whether the transfer goes through.
+    fn transfer_fee(&self, _from: ActorId, _to: ActorId, _value: U256) -> Option<(ActorId, U256)> {
+        None
+    }
+
+    /// Called once the `fee` returned by [`Hooks::transfer_fee`] for a
+    /// transfer from `from` to `to` was credited to `treasury`. Events may be
+    /// emitted on `route`.
+    fn fee_charged(
+        &self,
+        _route: &'static [u8],
+        _from: ActorId,
+        _to: ActorId,
+        _treasury: ActorId,
+        _fee: U256,
+    ) {
+    }
+}
+
+impl Hooks for () {}
+
+fn hooks() -> &'static dyn Hooks {
+    unsafe { HOOKS }
+}
+
+/// Returns the result of `f`, unless the program opted into panicking on
+/// errors with [`Service::with_panicking_errors`]. Extending services route
+/// their own errors through it too, so that the switch covers them.
+pub fn checked<T, E: Debug>(
+    f: impl FnOnce() -> core::result::Result<T, E>,
+) -> core::result::Result<T, E> {
+    if unsafe { PANICKING_ERRORS } {
+        Ok(utils::panicking(f))
+    } else {
+        f()
+    }
+}
+
+/// Transfers the fee returned by [`Hooks::transfer_fee`] to its treasury out
+/// of what the recipient of a transfer received, returning the event to emit
+/// for it.
+fn charge_fee(
+    route: &'static [u8],
+    from: ActorId,
+    to: ActorId,
+    value: U256,
+) -> utils::Result<Option<Event>> {
+    let Some((treasury, fee)) = hooks().transfer_fee(from, to, value) else {
+        return Ok(None);
+    };
+    hooks().before_transfer(to, treasury, fee);
+    if !funcs::transfer(Storage::balances(), to, treasury, fee)? {
+        return Ok(None);
+    }
+    hooks().after_transfer(route, to, treasury, fee);
+    hooks().fee_charged(route, from, to, treasury, fee);
+
+    Ok(Some(Event::Transfer {
+        from: to,
+        to: treasury,
+        value: fee,
+    }))
+}
+
+/// Moves `value` from `from` to `to` through the hooks like any transfer, but
+/// without emitting `Transfer`, for the value `transfer_and_call` escrows.
+fn escrow_transfer(
+    route: &'static [u8],
+    from: ActorId,
+    to: ActorId,
+    value: U256,
+) -> utils::Result<bool> {
+    hooks().before_transfer(from, to, value);
+    let mutated = funcs::transfer(Storage::balances(), from, to, value)?;
+    if mutated {
+        hooks().after_transfer(route, from, to, value);
+    }
+
+    Ok(mutated)
+}
+
+/// Moves `value` out of the escrow of `transfer_and_call` to `to` through
+/// [`Hooks::before_escrow_release`], so that nothing can keep it there.
+fn release_escrow(route: &'static [u8], to: ActorId, value: U256) {
+    let escrow = exec::program_id();
+    hooks().before_escrow_release(escrow, to, value);
+    // The program's own balance is only spent by the escrows that funded it,
+    // so it still covers `value`.
+    let mutated =
+        funcs::transfer(Storage::balances(), escrow, to, value).expect("Escrow covers the value");
+    if mutated {
+        hooks().after_transfer(route, escrow, to, value);
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct Storage {
-    balances: HashMap<ActorId, U256>,
-    allowances: HashMap<(ActorId, ActorId), U256>,
+    balances: utils::BalancesMap,
+    allowances: utils::AllowancesMap,
+    nonces: utils::NoncesMap,
+    held: utils::HeldMap,
+    holds: HashMap<U256, utils::Hold>,
     meta: Metadata,
     total_supply: U256,
 }
@@ -29,10 +168,18 @@ impl Storage {
     pub fn get() -> &'static Self {
         unsafe { STORAGE.as_ref().expect("Storage is not initialized") }
     }
-    pub fn balances() -> &'static mut HashMap<ActorId, U256> {
+    pub fn balances() -> &'static mut utils::BalancesMap {
         let storage = unsafe { STORAGE.as_mut().expect("Storage is not initialized") };
         &mut storage.balances
     }
+    pub fn allowances() -> &'static mut utils::AllowancesMap {
+        let storage = unsafe { STORAGE.as_mut().expect("Storage is not initialized") };
+        &mut storage.allowances
+    }
+    pub fn held() -> &'static mut utils::HeldMap {
+        let storage = unsafe { STORAGE.as_mut().expect("Storage is not initialized") };
+        &mut storage.held
+    }
     pub fn total_supply() -> &'static mut U256 {
         let storage = unsafe { STORAGE.as_mut().expect("Storage is not initialized") };
         &mut storage.total_supply
@@ -59,6 +206,20 @@ pub enum Event {
         to: ActorId,
         value: U256,
     },
+    HoldCreated {
+        operation_id: U256,
+        holder: ActorId,
+        notary: ActorId,
+        value: U256,
+        expiry: u64,
+    },
+    HoldExecuted {
+        operation_id: U256,
+        to: ActorId,
+    },
+    HoldReleased {
+        operation_id: U256,
+    },
 }
 
 #[derive(Clone)]
@@ -82,14 +243,35 @@ impl Service {
         }
         Self
     }
+
+    /// Makes methods panic on errors instead of returning them, as they did
+    /// before errors were returned, for programs whose callers rely on that.
+    pub fn with_panicking_errors(self) -> Self {
+        unsafe {
+            PANICKING_ERRORS = true;
+        }
+        self
+    }
+
+    /// Installs the hooks of the extending service, see [`Hooks`].
+    pub fn with_hooks(self, hooks: &'static dyn Hooks) -> Self {
+        unsafe {
+            HOOKS = hooks;
+        }
+        self
+    }
 }
 
 #[service(events = Event)]
 impl Service {
     #[export]
     pub fn approve(&mut self, spender: ActorId, value: U256) -> bool {
+        hooks().before_message();
         let owner = msg::source();
         let storage = Storage::get_mut();
+        if value > funcs::allowance(&storage.allowances, owner, spender) {
+            hooks().before_call();
+        }
         let mutated = funcs::approve(&mut storage.allowances, owner, spender, value);
 
         if mutated {
@@ -104,26 +286,258 @@ impl Service {
         mutated
     }
 
+    /// Adds `value` to the allowance of `spender` over the sender's tokens.
+    /// Emits `Approval` with the resulting allowance.
     #[export]
-    pub fn transfer(&mut self, to: ActorId, value: U256) -> bool {
-        let from = msg::source();
+    pub fn increase_allowance(&mut self, spender: ActorId, value: U256) -> utils::Result<bool> {
+        hooks().before_message();
+        hooks().before_call();
+        let owner = msg::source();
+        let storage = Storage::get_mut();
+        let mutated =
+            checked(|| funcs::increase_allowance(&mut storage.allowances, owner, spender, value))?;
+
+        if mutated {
+            self.emit_event(Event::Approval {
+                owner,
+                spender,
+                value: funcs::allowance(&storage.allowances, owner, spender),
+            })
+            .expect("Notification Error");
+        }
+
+        Ok(mutated)
+    }
+
+    /// Subtracts `value` from the allowance of `spender` over the sender's tokens.
+    /// Emits `Approval` with the resulting allowance.
+    #[export]
+    pub fn decrease_allowance(&mut self, spender: ActorId, value: U256) -> utils::Result<bool> {
+        hooks().before_message();
+        let owner = msg::source();
         let storage = Storage::get_mut();
         let mutated =
-            utils::panicking(move || funcs::transfer(&mut storage.balances, from, to, value));
+            checked(|| funcs::decrease_allowance(&mut storage.allowances, owner, spender, value))?;
+
+        if mutated {
+            self.emit_event(Event::Approval {
+                owner,
+                spender,
+                value: funcs::allowance(&storage.allowances, owner, spender),
+            })
+            .expect("Notification Error");
+        }
+
+        Ok(mutated)
+    }
+
+    /// Removes every allowance over the sender's tokens, emitting a zero
+    /// `Approval` for each spender.
+    #[export]
+    pub fn revoke_all_allowances(&mut self) -> bool {
+        hooks().before_message();
+        let owner = msg::source();
+        let storage = Storage::get_mut();
+        let spenders = funcs::revoke_all_allowances(&mut storage.allowances, owner);
+
+        for spender in &spenders {
+            self.emit_event(Event::Approval {
+                owner,
+                spender: *spender,
+                value: U256::zero(),
+            })
+            .expect("Notification Error");
+        }
+
+        !spenders.is_empty()
+    }
+
+    /// Sets `value` as the allowance of `spender` over the `owner`'s tokens,
+    /// given the owner's sr25519 or ed25519 signature of the permit.
+    /// Anyone can submit the permit; emits `Approval` if the allowance changed.
+    #[export]
+    pub fn permit(
+        &mut self,
+        owner: ActorId,
+        spender: ActorId,
+        value: U256,
+        deadline: u64,
+        nonce: U256,
+        signature: [u8; 64],
+    ) -> utils::Result<bool> {
+        hooks().before_message();
+        let storage = Storage::get_mut();
+        if value > funcs::allowance(&storage.allowances, owner, spender) {
+            hooks().before_call();
+        }
+        let permit = utils::Permit {
+            owner,
+            spender,
+            value,
+            deadline,
+            nonce,
+        };
+        let mutated = checked(move || {
+            funcs::permit(
+                &mut storage.allowances,
+                &mut storage.nonces,
+                exec::program_id(),
+                exec::block_timestamp(),
+                &permit,
+                &signature,
+            )
+        })?;
 
         if mutated {
+            self.emit_event(Event::Approval {
+                owner,
+                spender,
+                value,
+            })
+            .expect("Notification Error");
+        }
+
+        Ok(mutated)
+    }
+
+    #[export]
+    pub fn transfer(&mut self, to: ActorId, value: U256) -> utils::Result<bool> {
+        hooks().before_message();
+        hooks().before_call();
+        let from = msg::source();
+        let storage = Storage::get_mut();
+        hooks().before_transfer(from, to, value);
+        let mutated = checked(move || funcs::transfer(&mut storage.balances, from, to, value))?;
+
+        if mutated {
+            hooks().after_transfer(self.route(), from, to, value);
             self.emit_event(Event::Transfer { from, to, value })
                 .expect("Notification Error");
+            if let Some(event) = checked(|| charge_fee(self.route(), from, to, value))? {
+                self.emit_event(event).expect("Notification Error");
+            }
         }
 
-        mutated
+        Ok(mutated)
+    }
+
+    /// Transfers `value` to the `to` program and calls its
+    /// `VftReceiver/OnVftReceived(from, value, data)` hook, with the `value`
+    /// it's left with once charged the transfer fee. The value is kept in
+    /// escrow by this program until the receiver replies, and is returned to
+    /// the sender with `false` unless the receiver replies `true`. [`Hooks`]
+    /// see the transfers to and out of the escrow as they happen.
+    #[export]
+    pub async fn transfer_and_call(
+        &mut self,
+        to: ActorId,
+        value: U256,
+        data: Vec<u8>,
+    ) -> utils::Result<bool> {
+        hooks().before_message();
+        hooks().before_call();
+        let from = msg::source();
+        let escrow = exec::program_id();
+        // Checked against `to` up front, as the receiver gets called only if
+        // the transfer to it is allowed.
+        hooks().before_transfer(from, to, value);
+        let mutated = checked(|| escrow_transfer(self.route(), from, escrow, value))?;
+
+        if !mutated {
+            return Ok(false);
+        }
+
+        let fee = hooks()
+            .transfer_fee(from, to, value)
+            .map(|(treasury, fee)| (treasury, fee.min(value)))
+            .filter(|(_, fee)| !fee.is_zero());
+        let received = value - fee.map(|(_, fee)| fee).unwrap_or_default();
+        let payload = (
+            utils::RECEIVER_SERVICE,
+            utils::RECEIVER_METHOD,
+            from,
+            received,
+            data,
+        )
+            .encode();
+
+        let accepted = match msg::send_bytes_for_reply(to, payload, 0, 0) {
+            Ok(reply) => reply
+                .await
+                .ok()
+                .and_then(|reply| <(String, String, bool)>::decode(&mut reply.as_slice()).ok())
+                .is_some_and(|(service, method, accepted)| {
+                    service == utils::RECEIVER_SERVICE
+                        && method == utils::RECEIVER_METHOD
+                        && accepted
+                }),
+            Err(_) => false,
+        };
+
+        if !accepted {
+            release_escrow(self.route(), from, value);
+            return Ok(false);
+        }
+
+        release_escrow(self.route(), to, received);
+        self.emit_event(Event::Transfer { from, to, value })
+            .expect("Notification Error");
+        // Paid straight out of escrow, as `to` may not be able to pay it by now.
+        if let Some((treasury, fee)) = fee {
+            release_escrow(self.route(), treasury, fee);
+            hooks().fee_charged(self.route(), from, to, treasury, fee);
+            self.emit_event(Event::Transfer {
+                from: to,
+                to: treasury,
+                value: fee,
+            })
+            .expect("Notification Error");
+        }
+
+        Ok(true)
+    }
+
+    /// Transfers to several recipients at once, emitting a `Transfer` event
+    /// for each of them. Fails as a whole if the sender's balance doesn't
+    /// cover the batch.
+    #[export]
+    pub fn transfer_batch(&mut self, transfers: Vec<(ActorId, U256)>) -> utils::Result<bool> {
+        hooks().before_message();
+        hooks().before_call();
+        let from = msg::source();
+        for (to, value) in &transfers {
+            hooks().before_transfer(from, *to, *value);
+        }
+        let mutated = checked(|| funcs::transfer_batch(Storage::balances(), from, &transfers))?;
+
+        for (to, value) in transfers {
+            if to == from || value.is_zero() {
+                continue;
+            }
+            hooks().after_transfer(self.route(), from, to, value);
+            self.emit_event(Event::Transfer { from, to, value })
+                .expect("Notification Error");
+            if let Some(event) = checked(|| charge_fee(self.route(), from, to, value))? {
+                self.emit_event(event).expect("Notification Error");
+            }
+        }
+
+        Ok(mutated)
     }
 
     #[export]
-    pub fn transfer_from(&mut self, from: ActorId, to: ActorId, value: U256) -> bool {
+    pub fn transfer_from(
+        &mut self,
+        from: ActorId,
+        to: ActorId,
+        value: U256,
+    ) -> utils::Result<bool> {
+        hooks().before_message();
+        hooks().before_call();
         let spender = msg::source();
         let storage = Storage::get_mut();
-        let mutated = utils::panicking(move || {
+        hooks().before_transfer(from, to, value);
+        let mutated = checked(move || {
             funcs::transfer_from(
                 &mut storage.allowances,
                 &mut storage.balances,
@@ -132,48 +546,230 @@ impl Service {
                 to,
                 value,
             )
-        });
+        })?;
 
         if mutated {
+            hooks().after_transfer(self.route(), from, to, value);
             self.emit_event(Event::Transfer { from, to, value })
                 .expect("Notification Error");
+            if let Some(event) = checked(|| charge_fee(self.route(), from, to, value))? {
+                self.emit_event(event).expect("Notification Error");
+            }
         }
 
-        mutated
+        Ok(mutated)
+    }
+
+    /// Puts `value` of the sender's balance on hold under `operation_id`,
+    /// until `notary` executes or releases it. Held value can't be spent but
+    /// is still counted by `balance_of`. Anyone may release the hold after
+    /// `expiry` (block timestamp in ms), `0` meaning never.
+    #[export]
+    pub fn hold(
+        &mut self,
+        operation_id: U256,
+        notary: ActorId,
+        value: U256,
+        expiry: u64,
+    ) -> utils::Result<bool> {
+        hooks().before_message();
+        hooks().before_call();
+        let holder = msg::source();
+        let storage = Storage::get_mut();
+        let hold = utils::Hold {
+            holder,
+            notary,
+            value,
+            expiry,
+        };
+        let mutated = checked(|| {
+            funcs::hold(
+                &mut storage.balances,
+                &mut storage.held,
+                &mut storage.holds,
+                operation_id,
+                hold,
+                exec::block_timestamp(),
+            )
+        })?;
+
+        if mutated {
+            self.emit_event(Event::HoldCreated {
+                operation_id,
+                holder,
+                notary,
+                value,
+                expiry,
+            })
+            .expect("Notification Error");
+        }
+
+        Ok(mutated)
+    }
+
+    /// Transfers the value held under `operation_id` to `to`. Callable by the
+    /// notary of the hold until it expires.
+    #[export]
+    pub fn execute_hold(&mut self, operation_id: U256, to: ActorId) -> utils::Result<bool> {
+        hooks().before_message();
+        hooks().before_call();
+        let storage = Storage::get_mut();
+        if let Some(hold) = storage.holds.get(&operation_id) {
+            hooks().before_transfer(hold.holder, to, hold.value);
+        }
+        let hold = checked(|| {
+            funcs::execute_hold(
+                &mut storage.balances,
+                &mut storage.held,
+                &mut storage.holds,
+                operation_id,
+                msg::source(),
+                to,
+                exec::block_timestamp(),
+            )
+        })?;
+
+        self.emit_event(Event::HoldExecuted { operation_id, to })
+            .expect("Notification Error");
+        if hold.holder != to {
+            hooks().after_transfer(self.route(), hold.holder, to, hold.value);
+            self.emit_event(Event::Transfer {
+                from: hold.holder,
+                to,
+                value: hold.value,
+            })
+            .expect("Notification Error");
+            if let Some(event) = checked(|| charge_fee(self.route(), hold.holder, to, hold.value))?
+            {
+                self.emit_event(event).expect("Notification Error");
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Returns the value held under `operation_id` to the holder. Callable by
+    /// the notary of the hold, or by anyone once it expired.
+    #[export]
+    pub fn release_hold(&mut self, operation_id: U256) -> utils::Result<bool> {
+        hooks().before_message();
+        hooks().before_call();
+        let storage = Storage::get_mut();
+        checked(|| {
+            funcs::release_hold(
+                &mut storage.balances,
+                &mut storage.held,
+                &mut storage.holds,
+                operation_id,
+                msg::source(),
+                exec::block_timestamp(),
+            )
+        })?;
+
+        self.emit_event(Event::HoldReleased { operation_id })
+            .expect("Notification Error");
+
+        Ok(true)
     }
 
     #[export]
     pub fn allowance(&self, owner: ActorId, spender: ActorId) -> U256 {
+        hooks().before_message();
         let storage = Storage::get();
         funcs::allowance(&storage.allowances, owner, spender)
     }
 
+    /// Returns the balance of `account`, including its balance on hold.
     #[export]
     pub fn balance_of(&self, account: ActorId) -> U256 {
+        hooks().before_message();
+        let storage = Storage::get();
+        funcs::total_balance_of(&storage.balances, &storage.held, account)
+    }
+
+    /// Returns up to `limit` spenders of `owner` with their allowances,
+    /// skipping the first `offset`. Spenders are ordered by id.
+    #[export]
+    pub fn allowances_of(&self, owner: ActorId, offset: u32, limit: u32) -> Vec<(ActorId, U256)> {
+        hooks().before_message();
+        let storage = Storage::get();
+        funcs::allowances_of(&storage.allowances, owner, offset, limit)
+    }
+
+    /// Returns the number of spenders with an allowance over the tokens of
+    /// `owner`.
+    #[export]
+    pub fn spenders_count(&self, owner: ActorId) -> u32 {
+        hooks().before_message();
+        let storage = Storage::get();
+        funcs::spenders_count(&storage.allowances, owner)
+    }
+
+    #[export]
+    pub fn balance_on_hold(&self, account: ActorId) -> U256 {
+        hooks().before_message();
+        let storage = Storage::get();
+        funcs::balance_on_hold(&storage.held, account)
+    }
+
+    /// Returns the number of accounts with non-zero balance, including
+    /// balance on hold.
+    #[export]
+    pub fn holders_count(&self) -> u32 {
+        hooks().before_message();
+        let storage = Storage::get();
+        funcs::holders_count(&storage.balances, &storage.held)
+    }
+
+    /// Returns up to `limit` holders with their balances, including balance
+    /// on hold, skipping the first `offset`. Holders are ordered by id.
+    #[export]
+    pub fn holders(&self, offset: u32, limit: u32) -> Vec<(ActorId, U256)> {
+        hooks().before_message();
+        let storage = Storage::get();
+        funcs::holders(&storage.balances, &storage.held, offset, limit)
+    }
+
+    /// Returns the balance of `account` not on hold.
+    #[export]
+    pub fn spendable_balance_of(&self, account: ActorId) -> U256 {
+        hooks().before_message();
         let storage = Storage::get();
         funcs::balance_of(&storage.balances, account)
     }
 
+    /// Returns the nonce the next permit of `owner` must be signed with.
+    #[export]
+    pub fn nonces(&self, owner: ActorId) -> U256 {
+        hooks().before_message();
+        let storage = Storage::get();
+        funcs::nonce(&storage.nonces, owner)
+    }
+
     #[export]
     pub fn decimals(&self) -> &'static u8 {
+        hooks().before_message();
         let storage = Storage::get();
         &storage.meta.decimals
     }
 
     #[export]
     pub fn name(&self) -> &'static str {
+        hooks().before_message();
         let storage = Storage::get();
         &storage.meta.name
     }
 
     #[export]
     pub fn symbol(&self) -> &'static str {
+        hooks().before_message();
         let storage = Storage::get();
         &storage.meta.symbol
     }
 
     #[export]
     pub fn total_supply(&self) -> &'static U256 {
+        hooks().before_message();
         let storage = Storage::get();
         &storage.total_supply
     }
@@ -1,9 +1,17 @@
 use core::fmt::Debug;
 use gstd::ext;
 use sails_rs::prelude::*;
-use sails_rs::{collections::HashMap, prelude::*};
-pub type AllowancesMap = HashMap<(ActorId, ActorId), U256>;
-pub type BalancesMap = HashMap<ActorId, U256>;
+use sails_rs::{
+    collections::{BTreeMap, HashMap},
+    prelude::*,
+};
+/// Ordered, so that allowances of an owner are stored next to each other.
+pub type AllowancesMap = BTreeMap<(ActorId, ActorId), U256>;
+/// Ordered, so that holders are listed in the same order on every node.
+pub type BalancesMap = BTreeMap<ActorId, U256>;
+pub type HeldMap = BTreeMap<ActorId, U256>;
+pub type HoldsMap = HashMap<U256, Hold>;
+pub type NoncesMap = HashMap<ActorId, U256>;
 pub type Result<T, E = Error> = core::result::Result<T, E>;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Encode, Decode, TypeInfo)]
@@ -12,6 +20,50 @@ pub enum Error {
     InsufficientBalance,
     NumericOverflow,
     Underflow,
+    PermitExpired,
+    InvalidNonce,
+    InvalidSignature,
+    HoldExists,
+    NonexistentHold,
+    NotNotary,
+    HoldExpired,
+}
+
+/// Service and method routes a program must expose to accept tokens sent
+/// with `transfer_and_call`, replying `true` to keep them.
+pub const RECEIVER_SERVICE: &str = "VftReceiver";
+pub const RECEIVER_METHOD: &str = "OnVftReceived";
+
+/// Domain tag mixed into every permit payload, so that signatures made for
+/// other purposes can't be replayed as approvals.
+pub const PERMIT_DOMAIN: &[u8] = b"VFT_PERMIT";
+
+/// Approval signed off-chain by `owner` and submitted by anyone via `permit`.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct Permit {
+    pub owner: ActorId,
+    pub spender: ActorId,
+    pub value: U256,
+    /// Block timestamp (in milliseconds) after which the permit is rejected.
+    pub deadline: u64,
+    pub nonce: U256,
+}
+
+/// Balance of `holder` reserved until `notary` executes or releases it.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct Hold {
+    pub holder: ActorId,
+    pub notary: ActorId,
+    pub value: U256,
+    /// Block timestamp (in milliseconds) after which anyone may release the
+    /// hold and the notary can't execute it anymore, `0` for none.
+    pub expiry: u64,
+}
+
+impl Hold {
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expiry != 0 && now > self.expiry
+    }
 }
 
 pub fn panicking<T, E: Debug, F: FnOnce() -> Result<T, E>>(f: F) -> T {
@@ -1,7 +1,7 @@
 use super::utils::{Error, Result, *};
 use crate::Event;
 use sails_rs::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     prelude::*,
     ActorId,
 };
@@ -29,9 +29,82 @@ pub fn approve(
     Ok(true)
 }
 
+/// Approves or revokes `operator` for all tokens of `owner`, returning
+/// whether that changed anything.
+pub fn set_approval_for_all(
+    allowances: &mut HashMap<ActorId, HashSet<ActorId>>,
+    owner: ActorId,
+    operator: ActorId,
+    approved: bool,
+) -> Result<bool> {
+    if owner == operator {
+        return Ok(false);
+    }
+
+    if operator == ActorId::zero() {
+        return Err(Error::ZeroAddress);
+    }
+
+    if approved {
+        return Ok(allowances.entry(owner).or_default().insert(operator));
+    }
+
+    let Some(approvals) = allowances.get_mut(&owner) else {
+        return Ok(false);
+    };
+
+    let removed = approvals.remove(&operator);
+
+    if approvals.is_empty() {
+        allowances.remove(&owner);
+    }
+
+    Ok(removed)
+}
+
+/// Sets how much of token `id` `operator` may transfer on behalf of `owner`,
+/// a zero `amount` removing the approval. Returns whether that changed anything.
+pub fn approve_scoped(
+    scoped_allowances: &mut HashMap<(ActorId, ActorId, TokenId), U256>,
+    owner: ActorId,
+    operator: ActorId,
+    id: TokenId,
+    amount: U256,
+) -> Result<bool> {
+    if owner == operator {
+        return Ok(false);
+    }
+
+    if operator == ActorId::zero() {
+        return Err(Error::ZeroAddress);
+    }
+
+    let key = (owner, operator, id);
+
+    if amount.is_zero() {
+        Ok(scoped_allowances.remove(&key).is_some())
+    } else {
+        Ok(scoped_allowances.insert(key, amount) != Some(amount))
+    }
+}
+
+pub fn scoped_allowance(
+    scoped_allowances: &HashMap<(ActorId, ActorId, TokenId), U256>,
+    owner: &ActorId,
+    operator: &ActorId,
+    id: &TokenId,
+) -> U256 {
+    scoped_allowances
+        .get(&(*owner, *operator, *id))
+        .copied()
+        .unwrap_or_default()
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn transfer_from(
     balances: &mut HashMap<TokenId, HashMap<ActorId, U256>>,
     allowances: &HashMap<ActorId, HashSet<ActorId>>,
+    scoped_allowances: &mut HashMap<(ActorId, ActorId, TokenId), U256>,
     msg_src: ActorId,
     from: ActorId,
     to: ActorId,
@@ -42,9 +115,18 @@ pub fn transfer_from(
         return Err(Error::SenderAndRecipientAddressesAreSame);
     }
 
-    if from != msg_src && !is_approved(allowances, &from, &msg_src) {
-        return Err(Error::CallerIsNotOwnerOrApproved);
-    }
+    // Operators approved for all tokens aren't limited by scoped approvals.
+    let remaining_allowances = if from != msg_src && !is_approved(allowances, &from, &msg_src) {
+        Some(spend_scoped(
+            scoped_allowances,
+            from,
+            msg_src,
+            &ids,
+            &amounts,
+        )?)
+    } else {
+        None
+    };
 
     if to == ActorId::zero() {
         return Err(Error::ZeroAddress);
@@ -54,14 +136,20 @@ pub fn transfer_from(
         return Err(Error::LengthMismatch);
     }
 
-    for (id, amount) in ids.iter().zip(amounts.clone()) {
-        check_opportunity_transfer(&*balances, &from, id, amount)?;
-    }
+    check_opportunity_transfer(&*balances, &from, &ids, &amounts)?;
 
     for (i, id) in ids.iter().enumerate() {
         transfer_from_impl(balances, &from, &to, id, amounts[i]);
     }
 
+    for (id, allowance) in remaining_allowances.into_iter().flatten() {
+        if allowance.is_zero() {
+            scoped_allowances.remove(&(from, msg_src, id));
+        } else {
+            scoped_allowances.insert((from, msg_src, id), allowance);
+        }
+    }
+
     Ok(Event::Transfer {
         from,
         to,
@@ -70,6 +158,76 @@ pub fn transfer_from(
     })
 }
 
+/// Undoes a `transfer_from` of `ids` and `amounts` made by `msg_src`,
+/// giving back the scoped allowance it spent if `scoped`. Fails if `to`
+/// doesn't hold the tokens anymore.
+#[allow(clippy::too_many_arguments)]
+pub fn revert_transfer_from(
+    balances: &mut HashMap<TokenId, HashMap<ActorId, U256>>,
+    scoped_allowances: &mut HashMap<(ActorId, ActorId, TokenId), U256>,
+    msg_src: ActorId,
+    from: ActorId,
+    to: ActorId,
+    ids: &[TokenId],
+    amounts: &[U256],
+    scoped: bool,
+) -> Result<()> {
+    let mut returned = BTreeMap::new();
+
+    for (id, amount) in ids.iter().zip(amounts) {
+        let total: &mut U256 = returned.entry(*id).or_default();
+        *total = total.checked_add(*amount).ok_or(Error::NumericOverflow)?;
+
+        if get_balance(balances, &to, id) < *total {
+            return Err(Error::InsufficientBalance);
+        }
+    }
+
+    for (id, amount) in returned {
+        transfer_from_impl(balances, &to, &from, &id, amount);
+
+        if scoped {
+            let allowance = scoped_allowances.entry((from, msg_src, id)).or_default();
+            *allowance = allowance.saturating_add(amount);
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the scoped allowances `operator` is left with per token after
+/// transferring `amounts` of `ids`, without updating them yet.
+fn spend_scoped(
+    scoped_allowances: &HashMap<(ActorId, ActorId, TokenId), U256>,
+    owner: ActorId,
+    operator: ActorId,
+    ids: &[TokenId],
+    amounts: &[U256],
+) -> Result<BTreeMap<TokenId, U256>> {
+    let mut remaining = BTreeMap::new();
+
+    for (id, amount) in ids.iter().zip(amounts) {
+        let allowance = match remaining.get(id) {
+            Some(allowance) => *allowance,
+            None => *scoped_allowances
+                .get(&(owner, operator, *id))
+                .ok_or(Error::CallerIsNotOwnerOrApproved)?,
+        };
+
+        let allowance: U256 = allowance
+            .checked_sub(*amount)
+            .ok_or(Error::InsufficientAllowance)?;
+
+        remaining.insert(*id, allowance);
+    }
+
+    if remaining.is_empty() {
+        return Err(Error::CallerIsNotOwnerOrApproved);
+    }
+
+    Ok(remaining)
+}
+
 fn transfer_from_impl(
     balances: &mut HashMap<TokenId, HashMap<ActorId, U256>>,
     from: &ActorId,
@@ -91,17 +249,25 @@ fn transfer_from_impl(
         .or_insert(amount);
 }
 
+/// Checks `from` holds enough of every token, amounts of the same token
+/// adding up.
 fn check_opportunity_transfer(
     balances: &HashMap<TokenId, HashMap<ActorId, U256>>,
     from: &ActorId,
-    id: &TokenId,
-    amount: U256,
+    ids: &[TokenId],
+    amounts: &[U256],
 ) -> Result<(), Error> {
-    let balance = get_balance(balances, from, id).saturating_sub(amount);
+    let mut totals = BTreeMap::new();
+
+    for (id, amount) in ids.iter().zip(amounts) {
+        let total: &mut U256 = totals.entry(*id).or_default();
+        *total = total.checked_add(*amount).ok_or(Error::NumericOverflow)?;
 
-    if balance < amount {
-        return Err(Error::InsufficientBalance);
+        if get_balance(balances, from, id) < *total {
+            return Err(Error::InsufficientBalance);
+        }
     }
+
     Ok(())
 }
 
@@ -177,6 +343,7 @@ mod tests {
         // Creating maps for balances and allowances
         let mut balances = HashMap::new();
         let mut allowances = HashMap::new();
+        let mut scoped_allowances = HashMap::new();
 
         let token_id = 1.into();
         let owner = alice();
@@ -194,6 +361,7 @@ mod tests {
             funcs::transfer_from(
                 &mut balances,
                 &allowances,
+                &mut scoped_allowances,
                 approved,
                 owner,
                 recipient,
@@ -217,6 +385,7 @@ mod tests {
             funcs::transfer_from(
                 &mut balances,
                 &allowances,
+                &mut scoped_allowances,
                 owner,
                 owner,
                 owner,
@@ -231,6 +400,7 @@ mod tests {
             funcs::transfer_from(
                 &mut balances,
                 &allowances,
+                &mut scoped_allowances,
                 approved,
                 owner,
                 ActorId::zero(),
@@ -245,6 +415,20 @@ mod tests {
             funcs::transfer_from(
                 &mut balances,
                 &allowances,
+                &mut scoped_allowances,
+                approved,
+                owner,
+                recipient,
+                vec![token_id, token_id],
+                vec![30.into(), 30.into()]
+            ),
+            Err(Error::InsufficientBalance)
+        );
+        assert_eq!(
+            funcs::transfer_from(
+                &mut balances,
+                &allowances,
+                &mut scoped_allowances,
                 approved,
                 owner,
                 recipient,
@@ -253,6 +437,184 @@ mod tests {
             ),
             Err(Error::InsufficientBalance)
         );
+
+        // # Test case #5: Whole balance can be transferred.
+        assert!(funcs::transfer_from(
+            &mut balances,
+            &allowances,
+            &mut scoped_allowances,
+            approved,
+            owner,
+            recipient,
+            vec![token_id],
+            vec![50.into()]
+        )
+        .is_ok());
+        assert_eq!(get_balance(&balances, &owner, &token_id), U256::zero());
+    }
+
+    #[test]
+    fn set_approval_for_all() {
+        let mut allowances = HashMap::new();
+
+        // # Test case #1: Approve and revoke an operator.
+        {
+            assert_eq!(
+                funcs::set_approval_for_all(&mut allowances, alice(), bob(), true),
+                Ok(true)
+            );
+            assert_eq!(
+                funcs::set_approval_for_all(&mut allowances, alice(), bob(), true),
+                Ok(false)
+            );
+            assert!(is_approved(&allowances, &alice(), &bob()));
+
+            assert_eq!(
+                funcs::set_approval_for_all(&mut allowances, alice(), bob(), false),
+                Ok(true)
+            );
+            assert!(!is_approved(&allowances, &alice(), &bob()));
+            assert!(allowances.is_empty());
+        }
+
+        // # Test case #2: Revoking an operator that isn't approved changes nothing.
+        {
+            assert_eq!(
+                funcs::set_approval_for_all(&mut allowances, alice(), bob(), false),
+                Ok(false)
+            );
+        }
+
+        // # Test case #3: Zero address can't be an operator.
+        {
+            assert_eq!(
+                funcs::set_approval_for_all(&mut allowances, alice(), ActorId::zero(), true),
+                Err(Error::ZeroAddress)
+            );
+        }
+    }
+
+    #[test]
+    fn scoped_transfer_from() {
+        let mut balances = HashMap::new();
+        let mut allowances = HashMap::new();
+        let mut scoped_allowances = HashMap::new();
+
+        let (first, second): (TokenId, TokenId) = (1.into(), 2.into());
+        balances.insert(first, HashMap::from([(alice(), 100.into())]));
+        balances.insert(second, HashMap::from([(alice(), 100.into())]));
+
+        assert_eq!(
+            funcs::approve_scoped(&mut scoped_allowances, alice(), bob(), first, 30.into()),
+            Ok(true)
+        );
+        assert_eq!(
+            scoped_allowance(&scoped_allowances, &alice(), &bob(), &first),
+            30.into()
+        );
+
+        // # Test case #1: Scoped approval doesn't cover other tokens.
+        {
+            assert_eq!(
+                funcs::transfer_from(
+                    &mut balances,
+                    &allowances,
+                    &mut scoped_allowances,
+                    bob(),
+                    alice(),
+                    dave(),
+                    vec![first, second],
+                    vec![10.into(), 10.into()]
+                ),
+                Err(Error::CallerIsNotOwnerOrApproved)
+            );
+        }
+
+        // # Test case #2: Amounts of the same token add up within a batch.
+        {
+            assert_eq!(
+                funcs::transfer_from(
+                    &mut balances,
+                    &allowances,
+                    &mut scoped_allowances,
+                    bob(),
+                    alice(),
+                    dave(),
+                    vec![first, first],
+                    vec![20.into(), 20.into()]
+                ),
+                Err(Error::InsufficientAllowance)
+            );
+            assert_eq!(
+                scoped_allowance(&scoped_allowances, &alice(), &bob(), &first),
+                30.into()
+            );
+        }
+
+        // # Test case #3: Transfers consume the scoped allowance.
+        {
+            assert!(funcs::transfer_from(
+                &mut balances,
+                &allowances,
+                &mut scoped_allowances,
+                bob(),
+                alice(),
+                dave(),
+                vec![first],
+                vec![20.into()]
+            )
+            .is_ok());
+            assert_eq!(
+                scoped_allowance(&scoped_allowances, &alice(), &bob(), &first),
+                10.into()
+            );
+            assert_eq!(get_balance(&balances, &dave(), &first), 20.into());
+
+            assert!(funcs::transfer_from(
+                &mut balances,
+                &allowances,
+                &mut scoped_allowances,
+                bob(),
+                alice(),
+                dave(),
+                vec![first],
+                vec![10.into()]
+            )
+            .is_ok());
+            assert!(scoped_allowances.is_empty());
+        }
+
+        // # Test case #4: Operators approved for all tokens spend no scoped allowance.
+        {
+            funcs::approve_scoped(&mut scoped_allowances, alice(), bob(), second, 5.into())
+                .unwrap();
+            funcs::set_approval_for_all(&mut allowances, alice(), bob(), true).unwrap();
+
+            assert!(funcs::transfer_from(
+                &mut balances,
+                &allowances,
+                &mut scoped_allowances,
+                bob(),
+                alice(),
+                dave(),
+                vec![second],
+                vec![10.into()]
+            )
+            .is_ok());
+            assert_eq!(
+                scoped_allowance(&scoped_allowances, &alice(), &bob(), &second),
+                5.into()
+            );
+        }
+
+        // # Test case #5: Zero amount removes the scoped approval.
+        {
+            assert_eq!(
+                funcs::approve_scoped(&mut scoped_allowances, alice(), bob(), second, 0.into()),
+                Ok(true)
+            );
+            assert!(scoped_allowances.is_empty());
+        }
     }
 
     mod utils {
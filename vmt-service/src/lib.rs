@@ -5,7 +5,7 @@ use crate::utils::*;
 use core::fmt::Debug;
 use sails_rs::{
     collections::{HashMap, HashSet},
-    gstd::{msg, service},
+    gstd::{exec, msg, service},
     prelude::*,
 };
 
@@ -13,11 +13,49 @@ pub mod funcs;
 pub mod utils;
 
 static mut STORAGE: Option<Storage> = None;
+static mut HOOKS: &dyn Hooks = &();
+static mut PANICKING_ERRORS: bool = false;
+
+/// Lets a service extending [`Service`] guard the methods it inherits.
+/// Every method has a no-op default.
+///
+/// Rejections by hooks, such as those of a paused service, are panics and
+/// end in an error reply, as they aren't [`utils::Error`] variants.
+pub trait Hooks {
+    /// Called first in every method of [`Service`] that moves tokens or
+    /// grants an approval. May panic to reject the call. Approvals can always
+    /// be revoked or lowered.
+    fn before_call(&self) {}
+
+    /// Called after `amounts` of `ids` move from `from` to `to` in [`Service`],
+    /// including when a safe transfer is rolled back.
+    fn after_transfer(&self, _from: ActorId, _to: ActorId, _ids: &[TokenId], _amounts: &[U256]) {}
+}
+
+impl Hooks for () {}
+
+fn hooks() -> &'static dyn Hooks {
+    unsafe { HOOKS }
+}
+
+/// Returns the result of `f`, unless the program opted into panicking on
+/// errors with [`Service::with_panicking_errors`]. Extending services route
+/// their own errors through it too, so that the switch covers them.
+pub fn checked<T, E: Debug>(
+    f: impl FnOnce() -> core::result::Result<T, E>,
+) -> core::result::Result<T, E> {
+    if unsafe { PANICKING_ERRORS } {
+        Ok(utils::panicking(f))
+    } else {
+        f()
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct Storage {
     balances: HashMap<TokenId, HashMap<ActorId, U256>>,
     allowances: HashMap<ActorId, HashSet<ActorId>>,
+    scoped_allowances: HashMap<(ActorId, ActorId, TokenId), U256>,
     meta: Metadata,
     total_supply: HashMap<TokenId, U256>,
 }
@@ -37,6 +75,10 @@ impl Storage {
         let storage = unsafe { STORAGE.as_mut().expect("Storage is not initialized") };
         &mut storage.allowances
     }
+    pub fn scoped_allowances() -> &'static mut HashMap<(ActorId, ActorId, TokenId), U256> {
+        let storage = unsafe { STORAGE.as_mut().expect("Storage is not initialized") };
+        &mut storage.scoped_allowances
+    }
     pub fn total_supply() -> &'static mut HashMap<TokenId, U256> {
         let storage = unsafe { STORAGE.as_mut().expect("Storage is not initialized") };
         &mut storage.total_supply
@@ -57,6 +99,17 @@ pub enum Event {
         from: ActorId,
         to: ActorId,
     },
+    ApprovalForAll {
+        owner: ActorId,
+        operator: ActorId,
+        approved: bool,
+    },
+    ScopedApproval {
+        owner: ActorId,
+        operator: ActorId,
+        id: TokenId,
+        amount: U256,
+    },
     Transfer {
         from: ActorId,
         to: ActorId,
@@ -85,6 +138,104 @@ impl Service {
         }
         Self
     }
+
+    /// Restores the old behavior of panicking on errors, for callers that
+    /// expect a failed transfer or approval to end in an error reply.
+    pub fn with_panicking_errors(self) -> Self {
+        unsafe {
+            PANICKING_ERRORS = true;
+        }
+        self
+    }
+
+    /// Installs the hooks of the extending service, see [`Hooks`].
+    pub fn with_hooks(self, hooks: &'static dyn Hooks) -> Self {
+        unsafe {
+            HOOKS = hooks;
+        }
+        self
+    }
+
+    /// Makes the transfer of `transfer_from` to this program, then calls
+    /// `method` of the receiver hook of `to` with `args`. Returns the
+    /// `Transfer` event once the tokens are passed on to `to` if the receiver
+    /// accepts, otherwise returns them to `from`. Kept by this program until
+    /// the receiver replies, the tokens can't be moved by `to` meanwhile.
+    async fn safe_transfer(
+        from: ActorId,
+        to: ActorId,
+        ids: Vec<TokenId>,
+        amounts: Vec<U256>,
+        method: &str,
+        args: impl Encode,
+    ) -> utils::Result<Option<Event>> {
+        hooks().before_call();
+        let msg_src = msg::source();
+        let escrow = exec::program_id();
+        let storage = Storage::get_mut();
+        let scoped = from != msg_src && !funcs::is_approved(&storage.allowances, &from, &msg_src);
+        checked(|| {
+            // Checked against `to` up front, as the tokens go to the escrow first.
+            if from == to {
+                return Err(utils::Error::SenderAndRecipientAddressesAreSame);
+            }
+            if to == ActorId::zero() {
+                return Err(utils::Error::ZeroAddress);
+            }
+            funcs::transfer_from(
+                &mut storage.balances,
+                &storage.allowances,
+                &mut storage.scoped_allowances,
+                msg_src,
+                from,
+                escrow,
+                ids.clone(),
+                amounts.clone(),
+            )
+        })?;
+        hooks().after_transfer(from, escrow, &ids, &amounts);
+
+        let accepted = utils::receiver_accepts(to, method, args).await;
+
+        // Nobody but this program spends the escrow, so it still holds the tokens.
+        let storage = Storage::get_mut();
+        if accepted {
+            funcs::transfer_from(
+                &mut storage.balances,
+                &storage.allowances,
+                &mut storage.scoped_allowances,
+                escrow,
+                escrow,
+                to,
+                ids.clone(),
+                amounts.clone(),
+            )
+            .expect("Escrow covers the transfer");
+            hooks().after_transfer(escrow, to, &ids, &amounts);
+
+            return Ok(Some(Event::Transfer {
+                from,
+                to,
+                ids,
+                amounts,
+            }));
+        }
+
+        funcs::revert_transfer_from(
+            &mut storage.balances,
+            &mut storage.scoped_allowances,
+            msg_src,
+            from,
+            escrow,
+            &ids,
+            &amounts,
+            scoped,
+        )
+        .expect("Escrow covers the transfer");
+        hooks().after_transfer(escrow, from, &ids, &amounts);
+
+        Ok(None)
+    }
 }
 
 #[service(events = Event)]
@@ -92,37 +243,108 @@ impl Service {
     /// Approves an `ActorId` (account) to transfer tokens on behalf of the owner (sender).
     /// If the approval is successful, it emits an `Approval` event.
     #[export]
-    pub fn approve(&mut self, to: ActorId) -> bool {
+    pub fn approve(&mut self, to: ActorId) -> utils::Result<bool> {
+        hooks().before_call();
         let owner = msg::source();
         let storage = Storage::get_mut();
-        let mutated = utils::panicking(move || funcs::approve(&mut storage.allowances, owner, to));
+        let mutated = checked(move || funcs::approve(&mut storage.allowances, owner, to))?;
         if mutated {
             self.emit_event(Event::Approval { from: owner, to })
                 .expect("Notification Error");
         }
 
-        mutated
+        Ok(mutated)
+    }
+
+    /// Approves or revokes an operator (`operator`) for all tokens of the owner (sender).
+    /// Emits an `ApprovalForAll` event if the approval changed.
+    #[export]
+    pub fn set_approval_for_all(
+        &mut self,
+        operator: ActorId,
+        approved: bool,
+    ) -> utils::Result<bool> {
+        if approved {
+            hooks().before_call();
+        }
+        let owner = msg::source();
+        let storage = Storage::get_mut();
+        let mutated = checked(move || {
+            funcs::set_approval_for_all(&mut storage.allowances, owner, operator, approved)
+        })?;
+        if mutated {
+            self.emit_event(Event::ApprovalForAll {
+                owner,
+                operator,
+                approved,
+            })
+            .expect("Notification Error");
+        }
+
+        Ok(mutated)
+    }
+
+    /// Approves an operator (`operator`) to transfer up to `amount` of token `id` on behalf
+    /// of the owner (sender), replacing any previous amount; zero revokes the approval.
+    /// Emits a `ScopedApproval` event if the approval changed.
+    #[export]
+    pub fn approve_scoped(
+        &mut self,
+        operator: ActorId,
+        id: TokenId,
+        amount: U256,
+    ) -> utils::Result<bool> {
+        let owner = msg::source();
+        let storage = Storage::get_mut();
+        if amount > funcs::scoped_allowance(&storage.scoped_allowances, &owner, &operator, &id) {
+            hooks().before_call();
+        }
+        let mutated = checked(move || {
+            funcs::approve_scoped(&mut storage.scoped_allowances, owner, operator, id, amount)
+        })?;
+        if mutated {
+            self.emit_event(Event::ScopedApproval {
+                owner,
+                operator,
+                id,
+                amount,
+            })
+            .expect("Notification Error");
+        }
+
+        Ok(mutated)
     }
 
     /// Transfers tokens from one account (`from`) to another (`to`) if the sender is allowed.
     /// Emits a `Transfer` event after a successful transfer.
     #[export]
-    pub fn transfer_from(&mut self, from: ActorId, to: ActorId, id: TokenId, amount: U256) {
+    pub fn transfer_from(
+        &mut self,
+        from: ActorId,
+        to: ActorId,
+        id: TokenId,
+        amount: U256,
+    ) -> utils::Result<()> {
+        hooks().before_call();
         let msg_src = msg::source();
         let storage = Storage::get_mut();
-        let event = utils::panicking(move || {
+        let event = checked(move || {
             funcs::transfer_from(
                 &mut storage.balances,
                 &storage.allowances,
+                &mut storage.scoped_allowances,
                 msg_src,
                 from,
                 to,
                 vec![id],
                 vec![amount],
             )
-        });
+        })?;
+        hooks().after_transfer(from, to, &[id], &[amount]);
 
         self.emit_event(event).expect("Notification Error");
+
+        Ok(())
     }
 
     /// Transfers multiple tokens in batch from one account (`from`) to another (`to`).
@@ -134,22 +356,84 @@ impl Service {
         to: ActorId,
         ids: Vec<TokenId>,
         amounts: Vec<U256>,
-    ) {
+    ) -> utils::Result<()> {
+        hooks().before_call();
         let msg_src = msg::source();
         let storage = Storage::get_mut();
-        let event = utils::panicking(move || {
+        let event = checked(|| {
             funcs::transfer_from(
                 &mut storage.balances,
                 &storage.allowances,
+                &mut storage.scoped_allowances,
                 msg_src,
                 from,
                 to,
-                ids,
-                amounts,
+                ids.clone(),
+                amounts.clone(),
             )
-        });
+        })?;
+        hooks().after_transfer(from, to, &ids, &amounts);
 
         self.emit_event(event).expect("Notification Error");
+
+        Ok(())
+    }
+
+    /// Transfers like `transfer_from`, then calls the
+    /// `VmtReceiver/OnVmtReceived(operator, from, id, amount, data)` hook of `to` if it's a program.
+    /// The transfer is rolled back and `false` is returned unless the receiver replies `true`.
+    #[export]
+    pub async fn safe_transfer_from(
+        &mut self,
+        from: ActorId,
+        to: ActorId,
+        id: TokenId,
+        amount: U256,
+        data: Vec<u8>,
+    ) -> utils::Result<bool> {
+        let operator = msg::source();
+        let event = Service::safe_transfer(
+            from,
+            to,
+            vec![id],
+            vec![amount],
+            utils::RECEIVED_METHOD,
+            (operator, from, id, amount, data),
+        )
+        .await?;
+
+        let Some(event) = event else {
+            return Ok(false);
+        };
+        self.emit_event(event).expect("Notification Error");
+
+        Ok(true)
+    }
+
+    /// Transfers like `batch_transfer_from`, then calls the
+    /// `VmtReceiver/OnVmtBatchReceived(operator, from, ids, amounts, data)` hook of `to` if it's a program.
+    /// The transfer is rolled back and `false` is returned unless the receiver replies `true`.
+    #[export]
+    pub async fn safe_batch_transfer_from(
+        &mut self,
+        from: ActorId,
+        to: ActorId,
+        ids: Vec<TokenId>,
+        amounts: Vec<U256>,
+        data: Vec<u8>,
+    ) -> utils::Result<bool> {
+        let operator = msg::source();
+        let args = (operator, from, ids.clone(), amounts.clone(), data);
+        let event =
+            Service::safe_transfer(from, to, ids, amounts, utils::BATCH_RECEIVED_METHOD, args)
+                .await?;
+
+        let Some(event) = event else {
+            return Ok(false);
+        };
+        self.emit_event(event).expect("Notification Error");
+
+        Ok(true)
     }
 
     /// Checks if a specific operator (`operator`) is approved to transfer tokens on behalf of `account`.
@@ -160,6 +444,14 @@ impl Service {
         funcs::is_approved(&storage.allowances, &account, &operator)
     }
 
+    /// Returns how much of token `id` an operator (`operator`) may still transfer on behalf
+    /// of `account` under a scoped approval.
+    #[export]
+    pub fn scoped_allowance(&self, account: ActorId, operator: ActorId, id: TokenId) -> U256 {
+        let storage = Storage::get();
+        funcs::scoped_allowance(&storage.scoped_allowances, &account, &operator, &id)
+    }
+
     /// Returns the token balance of an account (`account`) for a specific token ID (`id`).
     #[export]
     pub fn balance_of(&self, account: ActorId, id: TokenId) -> U256 {
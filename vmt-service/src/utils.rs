@@ -1,5 +1,5 @@
 use core::fmt::Debug;
-use gstd::ext;
+use gstd::{ext, msg};
 use sails_rs::prelude::*;
 pub type Result<T, E = Error> = core::result::Result<T, E>;
 pub type TokenId = U256;
@@ -16,6 +16,44 @@ pub enum Error {
     LengthMismatch,
 }
 
+/// Service and method routes a program must expose to accept tokens sent
+/// with `safe_transfer_from` or `safe_mint`, replying `true` to keep them.
+pub const RECEIVER_SERVICE: &str = "VmtReceiver";
+pub const RECEIVED_METHOD: &str = "OnVmtReceived";
+/// Same as [`RECEIVED_METHOD`], for `safe_batch_transfer_from`.
+pub const BATCH_RECEIVED_METHOD: &str = "OnVmtBatchReceived";
+
+/// Gas reserved to handle the replies of receivers.
+const REPLY_DEPOSIT: u64 = 10_000_000_000;
+
+/// Calls `method` of the `VmtReceiver` service of `to` with `args`, returning
+/// whether it replied to accept the tokens. Accounts have no hook and always
+/// accept.
+pub async fn receiver_accepts(to: ActorId, method: &str, args: impl Encode) -> bool {
+    let payload = (RECEIVER_SERVICE, method, args).encode();
+
+    // Sent without gas, the call reaches an account right away, while a
+    // program can't run it and replies with an error.
+    let is_account = match msg::send_bytes_with_gas_for_reply(to, &payload, 0, 0, REPLY_DEPOSIT) {
+        Ok(reply) => reply.await.is_ok(),
+        Err(_) => false,
+    };
+    if is_account {
+        return true;
+    }
+
+    match msg::send_bytes_for_reply(to, payload, 0, REPLY_DEPOSIT) {
+        Ok(reply) => reply
+            .await
+            .ok()
+            .and_then(|reply| <(String, String, bool)>::decode(&mut reply.as_slice()).ok())
+            .is_some_and(|(service, reply_method, accepted)| {
+                service == RECEIVER_SERVICE && reply_method == method && accepted
+            }),
+        Err(_) => false,
+    }
+}
+
 pub fn panicking<T, E: Debug, F: FnOnce() -> Result<T, E>>(f: F) -> T {
     match f() {
         Ok(v) => v,
@@ -13,6 +13,39 @@ pub mod funcs;
 pub mod utils;
 
 static mut STORAGE: Option<Storage> = None;
+static mut HOOKS: &dyn Hooks = &();
+static mut PANICKING_ERRORS: bool = false;
+
+/// Lets a service extending [`Service`] guard the methods it inherits.
+/// Every method has a no-op default.
+///
+/// A hook rejecting a call, e.g. while paused, panics whether or not errors
+/// are returned, there being no [`utils::Error`] variant for it.
+pub trait Hooks {
+    /// Called first in every method of [`Service`] that moves a token or
+    /// approves an account. May panic to reject the call. Approvals can
+    /// always be revoked.
+    fn before_call(&self) {}
+}
+
+impl Hooks for () {}
+
+fn hooks() -> &'static dyn Hooks {
+    unsafe { HOOKS }
+}
+
+/// Returns the result of `f`, unless the program opted into panicking on
+/// errors with [`Service::with_panicking_errors`]. Extending services route
+/// their own errors through it too, so that the switch covers them.
+pub fn checked<T, E: Debug>(
+    f: impl FnOnce() -> core::result::Result<T, E>,
+) -> core::result::Result<T, E> {
+    if unsafe { PANICKING_ERRORS } {
+        Ok(utils::panicking(f))
+    } else {
+        f()
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct Storage {
@@ -77,15 +110,35 @@ impl Service {
         }
         Self
     }
+
+    /// Keeps failed `approve` and transfers panicking, as before they
+    /// returned an error.
+    pub fn with_panicking_errors(self) -> Self {
+        unsafe {
+            PANICKING_ERRORS = true;
+        }
+        self
+    }
+
+    /// Installs the hooks of the extending service, see [`Hooks`].
+    pub fn with_hooks(self, hooks: &'static dyn Hooks) -> Self {
+        unsafe {
+            HOOKS = hooks;
+        }
+        self
+    }
 }
 
 #[service(events = Event)]
 impl Service {
     #[export]
-    pub fn approve(&mut self, approved: ActorId, token_id: TokenId) {
+    pub fn approve(&mut self, approved: ActorId, token_id: TokenId) -> utils::Result<()> {
+        if !approved.is_zero() {
+            hooks().before_call();
+        }
         let source = msg::source();
         let owner = funcs::owner_of(&Storage::get().owner_by_id, token_id);
-        utils::panicking(move || {
+        checked(move || {
             funcs::approve(
                 Storage::token_approvals(),
                 source,
@@ -93,19 +146,22 @@ impl Service {
                 approved,
                 token_id,
             )
-        });
+        })?;
         self.emit_event(Event::Approval {
             owner,
             approved,
             token_id,
         })
         .expect("Notification Error");
+
+        Ok(())
     }
 
     #[export]
-    pub fn transfer(&mut self, to: ActorId, token_id: TokenId) {
+    pub fn transfer(&mut self, to: ActorId, token_id: TokenId) -> utils::Result<()> {
+        hooks().before_call();
         let source = msg::source();
-        utils::panicking(move || {
+        checked(move || {
             funcs::transfer(
                 Storage::token_approvals(),
                 Storage::owner_by_id(),
@@ -114,7 +170,7 @@ impl Service {
                 to,
                 token_id,
             )
-        });
+        })?;
 
         self.emit_event(Event::Transfer {
             from: source,
@@ -122,12 +178,20 @@ impl Service {
             token_id,
         })
         .expect("Notification Error");
+
+        Ok(())
     }
 
     #[export]
-    pub fn transfer_from(&mut self, from: ActorId, to: ActorId, token_id: TokenId) {
+    pub fn transfer_from(
+        &mut self,
+        from: ActorId,
+        to: ActorId,
+        token_id: TokenId,
+    ) -> utils::Result<()> {
+        hooks().before_call();
         let source = msg::source();
-        utils::panicking(move || {
+        checked(move || {
             funcs::transfer_from(
                 Storage::token_approvals(),
                 Storage::owner_by_id(),
@@ -137,10 +201,12 @@ impl Service {
                 to,
                 token_id,
             )
-        });
+        })?;
 
         self.emit_event(Event::Transfer { from, to, token_id })
             .expect("Notification Error");
+
+        Ok(())
     }
 
     #[export]
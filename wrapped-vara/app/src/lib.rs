@@ -0,0 +1,19 @@
+#![no_std]
+#![allow(clippy::new_without_default)]
+
+use sails_rs::prelude::*;
+mod services;
+use services::wrapped_vara::WrappedVaraService;
+pub struct WrappedVaraProgram(());
+
+#[program]
+impl WrappedVaraProgram {
+    pub fn new(name: String, symbol: String) -> Self {
+        WrappedVaraService::init(name, symbol);
+        Self(())
+    }
+
+    pub fn vft(&self) -> WrappedVaraService {
+        WrappedVaraService::new()
+    }
+}
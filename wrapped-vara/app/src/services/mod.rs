@@ -0,0 +1,2 @@
+pub mod utils;
+pub mod wrapped_vara;
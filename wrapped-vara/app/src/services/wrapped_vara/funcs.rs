@@ -0,0 +1,120 @@
+use sails_rs::prelude::*;
+use vft_service::{
+    funcs,
+    utils::{Error, Result, *},
+};
+
+pub fn deposit(
+    balances: &mut BalancesMap,
+    total_supply: &mut U256,
+    to: ActorId,
+    value: u128,
+) -> Result<bool> {
+    if value == 0 {
+        return Ok(false);
+    }
+
+    let value = U256::from(value);
+
+    let new_total_supply = total_supply
+        .checked_add(value)
+        .ok_or(Error::NumericOverflow)?;
+
+    let new_to = funcs::balance_of(balances, to)
+        .checked_add(value)
+        .ok_or(Error::NumericOverflow)?;
+
+    balances.insert(to, new_to);
+    *total_supply = new_total_supply;
+
+    Ok(true)
+}
+
+/// Burns `value` of `from`, returning the native value to send back.
+pub fn withdraw(
+    balances: &mut BalancesMap,
+    total_supply: &mut U256,
+    from: ActorId,
+    value: U256,
+) -> Result<u128> {
+    if value.is_zero() {
+        return Ok(0);
+    }
+
+    let native = u128::try_from(value).map_err(|_| Error::InsufficientBalance)?;
+
+    let new_from = funcs::balance_of(balances, from)
+        .checked_sub(value)
+        .ok_or(Error::InsufficientBalance)?;
+
+    let new_total_supply = total_supply.checked_sub(value).ok_or(Error::Underflow)?;
+
+    if !new_from.is_zero() {
+        balances.insert(from, new_from);
+    } else {
+        balances.remove(&from);
+    }
+
+    *total_supply = new_total_supply;
+
+    Ok(native)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alice() -> ActorId {
+        1u64.into()
+    }
+
+    #[test]
+    fn deposit_and_withdraw() {
+        let mut balances = BalancesMap::new();
+        let mut total_supply = U256::zero();
+
+        // # Test case #1.
+        // Zero deposit and withdrawal change nothing.
+        {
+            assert_eq!(
+                deposit(&mut balances, &mut total_supply, alice(), 0),
+                Ok(false)
+            );
+            assert_eq!(
+                withdraw(&mut balances, &mut total_supply, alice(), U256::zero()),
+                Ok(0)
+            );
+            assert!(balances.is_empty());
+        }
+
+        // # Test case #2.
+        // Deposit mints value, withdrawal burns it.
+        {
+            assert_eq!(
+                deposit(&mut balances, &mut total_supply, alice(), 100),
+                Ok(true)
+            );
+            assert_eq!(total_supply, 100.into());
+            assert_eq!(
+                withdraw(&mut balances, &mut total_supply, alice(), 60.into()),
+                Ok(60)
+            );
+            assert_eq!(funcs::balance_of(&balances, alice()), 40.into());
+            assert_eq!(total_supply, 40.into());
+        }
+
+        // # Test case #3.
+        // Error on withdrawing more than balance.
+        {
+            assert_eq!(
+                withdraw(&mut balances, &mut total_supply, alice(), 41.into()),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(
+                withdraw(&mut balances, &mut total_supply, alice(), U256::MAX),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(total_supply, 40.into());
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use sails_rs::{
+    gstd::{msg, service},
+    prelude::*,
+};
+mod funcs;
+use crate::services;
+use vft_service::{Hooks, Service as VftService, Storage};
+
+/// Native VARA has 12 decimals.
+pub const DECIMALS: u8 = 12;
+
+#[event]
+#[derive(Encode, Decode, TypeInfo)]
+#[codec(crate = sails_rs::scale_codec)]
+#[scale_info(crate = sails_rs::scale_info)]
+pub enum Event {
+    Deposit { to: ActorId, value: U256 },
+    Withdrawal { from: ActorId, value: U256 },
+}
+
+/// VFT backed one-to-one by native value locked on the program: tokens are
+/// minted only by `deposit` and burned only by `withdraw`, so total supply
+/// always equals the value deposited and not yet withdrawn.
+#[derive(Clone)]
+pub struct WrappedVaraService {
+    vft: VftService,
+}
+
+impl WrappedVaraService {
+    pub fn new() -> Self {
+        Self {
+            vft: VftService::new(),
+        }
+    }
+
+    pub fn init(name: String, symbol: String) -> Self {
+        WrappedVaraService {
+            vft: <VftService>::init(name, symbol, DECIMALS).with_hooks(&WrappedVaraHooks),
+        }
+    }
+}
+
+impl From<WrappedVaraService> for VftService {
+    fn from(value: WrappedVaraService) -> Self {
+        value.vft
+    }
+}
+
+#[service(extends = VftService, events = Event)]
+impl WrappedVaraService {
+    /// Mints tokens equal to the value attached to the message.
+    #[export]
+    pub fn deposit(&mut self) -> bool {
+        let to = msg::source();
+        let value = msg::value();
+
+        let mutated = services::utils::panicking(|| {
+            funcs::deposit(Storage::balances(), Storage::total_supply(), to, value)
+        });
+        if mutated {
+            self.emit_event(Event::Deposit {
+                to,
+                value: value.into(),
+            })
+            .expect("Notification Error");
+        }
+        mutated
+    }
+
+    /// Burns `value` tokens and attaches the same native value to the reply,
+    /// which any caller receives without handling it.
+    #[export]
+    pub fn withdraw(&mut self, value: U256) -> CommandReply<bool> {
+        ensure_no_value();
+        let from = msg::source();
+
+        let native = services::utils::panicking(|| {
+            funcs::withdraw(Storage::balances(), Storage::total_supply(), from, value)
+        });
+        if native == 0 {
+            return CommandReply::new(false);
+        }
+
+        self.emit_event(Event::Withdrawal { from, value })
+            .expect("Notification Error");
+
+        CommandReply::new(true).with_value(native)
+    }
+}
+
+/// Value attached to any call but `deposit` would be locked without minting.
+fn ensure_no_value() {
+    if msg::value() > 0 {
+        panic!("Value is only accepted by deposit")
+    }
+}
+
+/// Rejects value attached to the inherited methods.
+struct WrappedVaraHooks;
+
+impl Hooks for WrappedVaraHooks {
+    fn before_message(&self) {
+        ensure_no_value();
+    }
+}
@@ -0,0 +1,4 @@
+#![no_std]
+
+// Incorporate code generated based on the IDL file
+include!(concat!(env!("OUT_DIR"), "/wrapped_vara_client.rs"));
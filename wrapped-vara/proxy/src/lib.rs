@@ -0,0 +1,60 @@
+//! Sails program calling wrapped VARA for its callers, to test withdrawing
+//! to a program.
+//!
+//! Initialized with the address of wrapped VARA, it deposits the value
+//! attached to `deposit` and withdraws to itself with `withdraw`, relaying
+//! the replies of wrapped VARA.
+#![no_std]
+#![allow(static_mut_refs)]
+#![allow(clippy::new_without_default)]
+
+use sails_rs::{calls::*, gstd::calls::GStdRemoting, gstd::msg, prelude::*};
+use wrapped_vara_client::{traits::Vft as _, Vft};
+
+static mut TARGET: ActorId = ActorId::zero();
+
+pub struct ProxyService(());
+
+impl ProxyService {
+    pub fn new() -> Self {
+        Self(())
+    }
+}
+
+#[service]
+impl ProxyService {
+    #[export]
+    pub async fn deposit(&mut self) -> bool {
+        Vft::new(GStdRemoting::new())
+            .deposit()
+            .with_value(msg::value())
+            .send_recv(unsafe { TARGET })
+            .await
+            .expect("Unable to deposit")
+    }
+
+    #[export]
+    pub async fn withdraw(&mut self, value: U256) -> bool {
+        Vft::new(GStdRemoting::new())
+            .withdraw(value)
+            .send_recv(unsafe { TARGET })
+            .await
+            .expect("Unable to withdraw")
+    }
+}
+
+pub struct ProxyProgram(());
+
+#[program]
+impl ProxyProgram {
+    pub fn new(target: ActorId) -> Self {
+        unsafe {
+            TARGET = target;
+        }
+        Self(())
+    }
+
+    pub fn proxy(&self) -> ProxyService {
+        ProxyService::new()
+    }
+}
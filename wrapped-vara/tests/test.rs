@@ -0,0 +1,196 @@
+use gtest::{Log, Program};
+use sails_rs::calls::*;
+use sails_rs::gtest::{calls::*, System};
+use sails_rs::{ActorId, Encode, U256};
+use wrapped_vara_client::{
+    traits::{Vft, WrappedVaraFactory},
+    Vft as VftClient, WrappedVaraFactory as Factory,
+};
+
+pub const ADMIN_ID: u64 = 10;
+pub const USER_ID: u64 = 11;
+pub const ONE_VARA: u128 = 1_000_000_000_000;
+
+/// Deploys a Sails program calling `wrapped_vara_id` for the sender.
+fn proxy(system: &System, wrapped_vara_id: ActorId) -> ActorId {
+    let proxy = Program::from_file(
+        system,
+        "../target/wasm32-gear/release/wrapped_vara_proxy.opt.wasm",
+    );
+    proxy.send_bytes(ADMIN_ID, ("New", wrapped_vara_id).encode());
+    system.run_next_block();
+    proxy.id()
+}
+
+/// Calls `method` of the proxy with `args` and `value`, returning whether it
+/// replied `true`.
+fn call_proxy(
+    system: &System,
+    proxy_id: ActorId,
+    method: &str,
+    args: impl Encode,
+    value: u128,
+) -> bool {
+    let proxy = system.get_program(proxy_id).unwrap();
+    proxy.send_bytes_with_value(USER_ID, ("Proxy", method, args).encode(), value);
+    let res = system.run_next_block();
+    let log = Log::builder()
+        .source(proxy_id)
+        .dest(USER_ID)
+        .payload(("Proxy", method, true));
+
+    res.contains(&log)
+}
+
+#[tokio::test]
+async fn test_deposit_and_withdraw() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000 * ONE_VARA);
+    system.mint_to(USER_ID, 1_000 * ONE_VARA);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/wrapped_vara.opt.wasm");
+
+    let wrapped_vara_factory = Factory::new(program_space.clone());
+    let wrapped_vara_id = wrapped_vara_factory
+        .new("Wrapped Vara".to_string(), "WVARA".to_string())
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+
+    let mut client = VftClient::new(program_space.clone());
+    let decimals = client.decimals().recv(wrapped_vara_id).await.unwrap();
+    assert_eq!(decimals, 12);
+
+    // deposit
+    let program_balance = program_space.system().balance_of(wrapped_vara_id);
+    let res = client
+        .deposit()
+        .with_args(|args| args.with_actor_id(USER_ID.into()))
+        .with_value(10 * ONE_VARA)
+        .send_recv(wrapped_vara_id)
+        .await
+        .unwrap();
+    assert!(res);
+    let balance = client
+        .balance_of(USER_ID.into())
+        .recv(wrapped_vara_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, (10 * ONE_VARA).into());
+    let total_supply = client.total_supply().recv(wrapped_vara_id).await.unwrap();
+    assert_eq!(total_supply, (10 * ONE_VARA).into());
+    assert_eq!(
+        program_space.system().balance_of(wrapped_vara_id),
+        program_balance + 10 * ONE_VARA
+    );
+
+    // value attached to other calls is rejected
+    let res = client
+        .transfer(ADMIN_ID.into(), ONE_VARA.into())
+        .with_args(|args| args.with_actor_id(USER_ID.into()))
+        .with_value(ONE_VARA)
+        .send_recv(wrapped_vara_id)
+        .await;
+    assert!(res.is_err());
+
+    let res = client
+        .decrease_allowance(ADMIN_ID.into(), ONE_VARA.into())
+        .with_args(|args| args.with_actor_id(USER_ID.into()))
+        .with_value(ONE_VARA)
+        .send_recv(wrapped_vara_id)
+        .await;
+    assert!(res.is_err());
+    let res = client
+        .revoke_all_allowances()
+        .with_args(|args| args.with_actor_id(USER_ID.into()))
+        .with_value(ONE_VARA)
+        .send_recv(wrapped_vara_id)
+        .await;
+    assert!(res.is_err());
+
+    // withdraw more than balance
+    let res = client
+        .withdraw((11 * ONE_VARA).into())
+        .with_args(|args| args.with_actor_id(USER_ID.into()))
+        .send_recv(wrapped_vara_id)
+        .await;
+    assert!(res.is_err());
+
+    // withdraw
+    let user_balance = program_space.system().balance_of(USER_ID);
+    let res = client
+        .withdraw((4 * ONE_VARA).into())
+        .with_args(|args| args.with_actor_id(USER_ID.into()))
+        .send_recv(wrapped_vara_id)
+        .await
+        .unwrap();
+    assert!(res);
+    let balance = client
+        .balance_of(USER_ID.into())
+        .recv(wrapped_vara_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, (6 * ONE_VARA).into());
+    let total_supply = client.total_supply().recv(wrapped_vara_id).await.unwrap();
+    assert_eq!(total_supply, (6 * ONE_VARA).into());
+    assert_eq!(
+        program_space.system().balance_of(wrapped_vara_id),
+        program_balance + 6 * ONE_VARA
+    );
+    assert!(program_space.system().balance_of(USER_ID) > user_balance + 3 * ONE_VARA);
+}
+
+#[tokio::test]
+async fn test_withdraw_to_program() {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ADMIN_ID, 1_000 * ONE_VARA);
+    system.mint_to(USER_ID, 1_000 * ONE_VARA);
+
+    let program_space = GTestRemoting::new(system, ADMIN_ID.into());
+    let code_id = program_space
+        .system()
+        .submit_code_file("../target/wasm32-gear/release/wrapped_vara.opt.wasm");
+
+    let wrapped_vara_factory = Factory::new(program_space.clone());
+    let wrapped_vara_id = wrapped_vara_factory
+        .new("Wrapped Vara".to_string(), "WVARA".to_string())
+        .send_recv(code_id, "123")
+        .await
+        .unwrap();
+    let proxy_id = proxy(program_space.system(), wrapped_vara_id);
+
+    // deposit through the program
+    let system = program_space.system();
+    assert!(call_proxy(system, proxy_id, "Deposit", (), 10 * ONE_VARA));
+    let client = VftClient::new(program_space.clone());
+    let balance = client
+        .balance_of(proxy_id)
+        .recv(wrapped_vara_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, (10 * ONE_VARA).into());
+    let program_balance = system.balance_of(wrapped_vara_id);
+
+    // withdraw to the program, receiving the value with the reply
+    let proxy_balance = system.balance_of(proxy_id);
+    let value = U256::from(4 * ONE_VARA);
+    assert!(call_proxy(system, proxy_id, "Withdraw", value, 0));
+    let balance = client
+        .balance_of(proxy_id)
+        .recv(wrapped_vara_id)
+        .await
+        .unwrap();
+    assert_eq!(balance, (6 * ONE_VARA).into());
+    let total_supply = client.total_supply().recv(wrapped_vara_id).await.unwrap();
+    assert_eq!(total_supply, (6 * ONE_VARA).into());
+    assert_eq!(system.balance_of(proxy_id), proxy_balance + 4 * ONE_VARA);
+    assert_eq!(
+        system.balance_of(wrapped_vara_id),
+        program_balance - 4 * ONE_VARA
+    );
+}